@@ -0,0 +1,49 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dlms_cosem::{Apdu, ObisMap};
+
+/// Build a load-profile-like data notification containing `n` nested registers.
+fn load_profile(n: u8) -> Vec<u8> {
+  #[rustfmt::skip]
+  let mut payload = vec![
+    0x0f, // Type (Data Notification)
+      0x00, 0x00, 0x55, 0x39, // Invoke ID & Priority
+      0x0c, // Date & Time (Length)
+        0x07, 0xe5, 0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0x00, 0xff, 0x88, 0x80,
+      0x02, // Type (Structure)
+        n, // Length
+  ];
+
+  for i in 0..n {
+    #[rustfmt::skip]
+    payload.extend([
+      0x02, // Type (Structure)
+        0x03, // Length
+          0x09, // Type (Octet String)
+            0x06, // Length
+              0x01, 0x00, 0x63, 0x01, i, 0xff,
+          0x09, // Type (Octet String)
+            0x0c, // Length
+              0x07, 0xe5, 0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0x00, 0xff, 0x88, 0x80,
+          0x02, // Type (Structure)
+            0x02, // Length
+              0x0f, // Type (Integer)
+                0xff,
+              0x16, // Type (Enum)
+                0x1e,
+    ]);
+  }
+
+  payload
+}
+
+fn parse(c: &mut Criterion) {
+  let payload = load_profile(200);
+
+  c.bench_function("Apdu::parse", |b| b.iter(|| Apdu::parse(black_box(&payload)).unwrap()));
+
+  let (_, apdu) = Apdu::parse(&payload).unwrap();
+  c.bench_function("ObisMap::parse", |b| b.iter(|| ObisMap::parse(black_box(&apdu)).unwrap()));
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
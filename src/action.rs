@@ -0,0 +1,110 @@
+use core::convert::TryFrom;
+
+use derive_try_from_primitive::TryFromPrimitive;
+use nom::{bytes::streaming::tag, combinator::fail, number::streaming::u8, IResult};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+  data::parse_optional, data_access_result::parse_get_data_result, CosemMethodDescriptor, Data, DataAccessResult,
+  InvokeIdAndPriority,
+};
+
+/// The result of invoking a method.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[repr(u8)]
+#[non_exhaustive]
+#[rustfmt::skip]
+pub enum ActionResult {
+  Success                 =   0,
+  HardwareFault           =   1,
+  TemporaryFailure        =   2,
+  ReadWriteDenied         =   3,
+  ObjectUndefined         =   4,
+  ObjectClassInconsistent =   9,
+  ObjectUnavailable       =  11,
+  TypeUnmatched           =  12,
+  ScopeOfAccessViolated   =  13,
+  DataBlockUnavailable    =  14,
+  LongActionAborted       =  15,
+  NoLongActionInProgress  =  16,
+  OtherReason             = 250,
+}
+
+impl ActionResult {
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, result) = u8(input)?;
+    match Self::try_from(result) {
+      Ok(result) => Ok((input, result)),
+      Err(_) => fail(input),
+    }
+  }
+}
+
+/// An `ACTION-Request` APDU.
+///
+/// Only the `action-request-normal` form is supported.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequest {
+  pub(crate) invoke_id_and_priority: InvokeIdAndPriority,
+  pub(crate) method_descriptor: CosemMethodDescriptor,
+  pub(crate) method_invocation_parameters: Option<Data>,
+}
+
+impl ActionRequest {
+  pub fn invoke_id_and_priority(&self) -> &InvokeIdAndPriority {
+    &self.invoke_id_and_priority
+  }
+
+  pub fn method_descriptor(&self) -> &CosemMethodDescriptor {
+    &self.method_descriptor
+  }
+
+  pub fn method_invocation_parameters(&self) -> Option<&Data> {
+    self.method_invocation_parameters.as_ref()
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, _) = tag([1])(input)?;
+    let (input, invoke_id_and_priority) = InvokeIdAndPriority::parse(input)?;
+    let (input, method_descriptor) = CosemMethodDescriptor::parse(input)?;
+    let (input, method_invocation_parameters) = parse_optional(Data::parse)(input)?;
+    Ok((input, Self { invoke_id_and_priority, method_descriptor, method_invocation_parameters }))
+  }
+}
+
+/// An `ACTION-Response` APDU.
+///
+/// Only the `action-response-normal` form is supported.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionResponse {
+  pub(crate) invoke_id_and_priority: InvokeIdAndPriority,
+  pub(crate) result: ActionResult,
+  pub(crate) return_parameters: Option<Result<Data, DataAccessResult>>,
+}
+
+impl ActionResponse {
+  pub fn invoke_id_and_priority(&self) -> &InvokeIdAndPriority {
+    &self.invoke_id_and_priority
+  }
+
+  pub fn result(&self) -> ActionResult {
+    self.result
+  }
+
+  /// The data returned by the method, or the reason why it could not be returned.
+  pub fn return_parameters(&self) -> Option<Result<&Data, DataAccessResult>> {
+    self.return_parameters.as_ref().map(|result| result.as_ref().map_err(|&err| err))
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, _) = tag([1])(input)?;
+    let (input, invoke_id_and_priority) = InvokeIdAndPriority::parse(input)?;
+    let (input, result) = ActionResult::parse(input)?;
+    let (input, return_parameters) = parse_optional(parse_get_data_result)(input)?;
+    Ok((input, Self { invoke_id_and_priority, result, return_parameters }))
+  }
+}
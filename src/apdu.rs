@@ -0,0 +1,360 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use aes::Aes128;
+use cipher::Key;
+use nom::{
+  combinator::{all_consuming, complete, fail},
+  multi::many1,
+  number::streaming::u8,
+  IResult,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::data_notification::DataNotification;
+use crate::general_glo_ciphering::{GeneralGloCipheringRef, DEFAULT_INVOCATION_COUNTER_WIDTH};
+use crate::{
+  map_nom_error, ActionRequest, ActionResponse, CosemAttributeDescriptor, Data, Error, EventNotification,
+  GeneralGloCiphering, GetRequest, GetResponse, Metadata, ReadRequest, ReadResponse, SetRequest, SetResponse,
+};
+
+/// Parse a single APDU spanning all of `bytes`, decrypting it if a `key` is given.
+///
+/// Without a key, a ciphered APDU is returned as [`Apdu::GeneralGloCiphering`].
+///
+/// ```
+/// use dlms_cosem::{parse, Apdu};
+///
+/// let bytes = [0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00, 0x04, 0xd2];
+/// let apdu = parse(&bytes, None).unwrap();
+/// assert!(matches!(apdu, Apdu::DataNotification(_)));
+///
+/// assert!(parse(&bytes[..5], None).is_err());
+/// ```
+pub fn parse(bytes: &[u8], key: Option<&Key<Aes128>>) -> Result<Apdu, Error> {
+  let (_, apdu) = match key {
+    Some(key) => map_nom_error(all_consuming(complete(|input| Apdu::parse_encrypted(input, key)))(bytes))?,
+    None => all_consuming(complete(Apdu::parse))(bytes).map_err(|_| Error::InvalidFormat)?,
+  };
+
+  Ok(apdu)
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Apdu {
+  ReadRequest(ReadRequest),
+  ReadResponse(ReadResponse),
+  DataNotification(DataNotification),
+  EventNotification(EventNotification),
+  GetRequest(GetRequest),
+  GetResponse(GetResponse),
+  SetRequest(SetRequest),
+  SetResponse(SetResponse),
+  ActionRequest(ActionRequest),
+  ActionResponse(ActionResponse),
+  GeneralGloCiphering(GeneralGloCiphering),
+}
+
+const GENERAL_GLO_CIPHERING_TAG: u8 = 219;
+
+/// Values used for decryption in place of the ones in a general-glo-ciphering APDU.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct CipheringOverrides {
+  pub(crate) system_title: Option<[u8; 8]>,
+  pub(crate) invocation_counter: Option<u32>,
+}
+
+impl Apdu {
+  pub fn parse_encrypted<'i>(input: &'i [u8], key: &Key<Aes128>) -> IResult<&'i [u8], Self, Error> {
+    let (input, (apdu, _)) =
+      Self::parse_with_meta(input, key, None, Default::default(), DEFAULT_INVOCATION_COUNTER_WIDTH, &mut Vec::new())?;
+    Ok((input, apdu))
+  }
+
+  /// Parse and decrypt a general-glo-ciphering APDU, returning its envelope together with the decrypted APDU.
+  ///
+  /// The payload of the returned envelope is empty.
+  pub fn parse_encrypted_with_envelope<'i>(
+    input: &'i [u8],
+    key: &Key<Aes128>,
+  ) -> IResult<&'i [u8], (GeneralGloCiphering, Self), Error> {
+    match input.first() {
+      Some(&GENERAL_GLO_CIPHERING_TAG) => {
+        let (input, (ciphering, apdu)) = Self::parse_ciphered(
+          input,
+          key,
+          None,
+          Default::default(),
+          DEFAULT_INVOCATION_COUNTER_WIDTH,
+          &mut Vec::new(),
+        )?;
+        Ok((input, (ciphering.envelope(), apdu)))
+      },
+      Some(_) => Err(nom::Err::Failure(Error::InvalidFormat)),
+      None => Err(nom::Err::Incomplete(nom::Needed::new(1))),
+    }
+  }
+
+  fn parse_ciphered<'i>(
+    input: &'i [u8],
+    key: &Key<Aes128>,
+    authentication_key: Option<&Key<Aes128>>,
+    overrides: CipheringOverrides,
+    counter_width: usize,
+    buffer: &mut Vec<u8>,
+  ) -> IResult<&'i [u8], (GeneralGloCipheringRef<'i>, Self), Error> {
+    // The frame has already been delimited by the data link layer, so a payload
+    // length exceeding it means the frame is malformed rather than incomplete.
+    let (input, mut ciphering) =
+      GeneralGloCipheringRef::parse(&input[1..], counter_width).map_err(|_| nom::Err::Failure(Error::InvalidFormat))?;
+
+    if let Some(system_title) = overrides.system_title {
+      ciphering.system_title = Some(system_title);
+    }
+    if let Some(invocation_counter) = overrides.invocation_counter {
+      ciphering.invocation_counter = Some(invocation_counter);
+    }
+
+    // Suite 2 uses AES-GCM-256, which cannot be decrypted with a 128-bit key.
+    match ciphering.security_control.suite_id() {
+      0 | 1 => (),
+      suite_id => return Err(nom::Err::Failure(Error::UnsupportedSuite(suite_id))),
+    }
+
+    ciphering.decrypt_into(key, authentication_key, buffer).map_err(|_| nom::Err::Failure(Error::DecryptionFailed))?;
+
+    let (_, apdu) =
+      all_consuming(complete(Apdu::parse))(buffer).map_err(|_| nom::Err::Failure(Error::InvalidFormat))?;
+    Ok((input, (ciphering, apdu)))
+  }
+
+  pub(crate) fn parse_with_meta<'i>(
+    input: &'i [u8],
+    key: &Key<Aes128>,
+    authentication_key: Option<&Key<Aes128>>,
+    overrides: CipheringOverrides,
+    counter_width: usize,
+    buffer: &mut Vec<u8>,
+  ) -> IResult<&'i [u8], (Self, Metadata), Error> {
+    match input.first() {
+      Some(&GENERAL_GLO_CIPHERING_TAG) => {
+        let (input, (ciphering, apdu)) =
+          Self::parse_ciphered(input, key, authentication_key, overrides, counter_width, buffer)?;
+        let meta = Metadata {
+          ciphered: true,
+          system_title: ciphering.system_title,
+          invocation_counter: ciphering.invocation_counter,
+          segment_count: 1,
+        };
+        Ok((input, (apdu, meta)))
+      },
+      // Plaintext APDUs are parsed directly from the borrowed input.
+      _ => {
+        let (input, apdu) = Self::parse(input).map_err(|_| nom::Err::Failure(Error::InvalidFormat))?;
+        Ok((
+          input,
+          (apdu, Metadata { ciphered: false, system_title: None, invocation_counter: None, segment_count: 1 }),
+        ))
+      },
+    }
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, tag) = u8(input)?;
+    match tag {
+      5 => {
+        let (input, read_request) = ReadRequest::parse(input)?;
+        Ok((input, Self::ReadRequest(read_request)))
+      },
+      12 => {
+        let (input, read_response) = ReadResponse::parse(input)?;
+        Ok((input, Self::ReadResponse(read_response)))
+      },
+      15 => {
+        let (input, data_notification) = DataNotification::parse(input)?;
+        Ok((input, Self::DataNotification(data_notification)))
+      },
+      192 => {
+        let (input, get_request) = GetRequest::parse(input)?;
+        Ok((input, Self::GetRequest(get_request)))
+      },
+      193 => {
+        let (input, set_request) = SetRequest::parse(input)?;
+        Ok((input, Self::SetRequest(set_request)))
+      },
+      194 => {
+        let (input, event_notification) = EventNotification::parse(input)?;
+        Ok((input, Self::EventNotification(event_notification)))
+      },
+      195 => {
+        let (input, action_request) = ActionRequest::parse(input)?;
+        Ok((input, Self::ActionRequest(action_request)))
+      },
+      196 => {
+        let (input, get_response) = GetResponse::parse(input)?;
+        Ok((input, Self::GetResponse(get_response)))
+      },
+      197 => {
+        let (input, set_response) = SetResponse::parse(input)?;
+        Ok((input, Self::SetResponse(set_response)))
+      },
+      199 => {
+        let (input, action_response) = ActionResponse::parse(input)?;
+        Ok((input, Self::ActionResponse(action_response)))
+      },
+      GENERAL_GLO_CIPHERING_TAG => {
+        let (input, general_glo_ciphering) = GeneralGloCiphering::parse(input)?;
+        Ok((input, Self::GeneralGloCiphering(general_glo_ciphering)))
+      },
+      _ => fail(input),
+    }
+  }
+
+  /// Parse all APDUs concatenated in `input`, consuming it completely.
+  ///
+  /// A decrypted general-glo-ciphering payload may contain more than one APDU, see
+  /// [`GeneralGloCiphering::decrypt`].
+  ///
+  /// ```
+  /// use dlms_cosem::Apdu;
+  ///
+  /// let notification = [0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00];
+  /// let (_, apdus) = Apdu::parse_all(&[notification, notification].concat()).unwrap();
+  /// assert_eq!(apdus.len(), 2);
+  /// ```
+  pub fn parse_all(input: &[u8]) -> IResult<&[u8], Vec<Self>> {
+    all_consuming(many1(complete(Self::parse)))(input)
+  }
+
+  /// The tag which identifies the type of this APDU on the wire.
+  ///
+  /// ```
+  /// use dlms_cosem::Apdu;
+  ///
+  /// let (_, apdu) = Apdu::parse(&[0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00]).unwrap();
+  /// assert_eq!(apdu.tag(), 15);
+  /// ```
+  pub fn tag(&self) -> u8 {
+    match self {
+      Self::ReadRequest(_) => 5,
+      Self::ReadResponse(_) => 12,
+      Self::DataNotification(_) => 15,
+      Self::GetRequest(_) => 192,
+      Self::SetRequest(_) => 193,
+      Self::EventNotification(_) => 194,
+      Self::ActionRequest(_) => 195,
+      Self::GetResponse(_) => 196,
+      Self::SetResponse(_) => 197,
+      Self::ActionResponse(_) => 199,
+      Self::GeneralGloCiphering(_) => GENERAL_GLO_CIPHERING_TAG,
+    }
+  }
+}
+
+/// A one-line summary of the APDU, e.g. for logging.
+///
+/// For a data notification, the number of items in its body is shown, without parsing them as registers.
+///
+/// ```
+/// use dlms_cosem::Apdu;
+///
+/// let (_, apdu) = Apdu::parse(&[0x0f, 0x00, 0x00, 0x55, 0x39, 0x00, 0x02, 0x00]).unwrap();
+/// assert_eq!(apdu.to_string(), "DataNotification(invoke_id=21817, 0 items)");
+/// ```
+impl fmt::Display for Apdu {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn attribute(f: &mut fmt::Formatter<'_>, descriptor: &CosemAttributeDescriptor) -> fmt::Result {
+      write!(f, "attribute={}/{}/{}", descriptor.class_id(), descriptor.instance_id(), descriptor.attribute_id())
+    }
+
+    match self {
+      Self::ReadRequest(request) => {
+        write!(f, "ReadRequest({} variables)", request.variable_access_specifications().len())
+      },
+      Self::ReadResponse(response) => write!(f, "ReadResponse({} results)", response.results().len()),
+      Self::DataNotification(notification) => {
+        write!(f, "DataNotification(invoke_id={}", notification.invoke_id())?;
+        if let Data::Structure(items) | Data::Array(items) = &notification.notification_body {
+          write!(f, ", {} items", items.len())?;
+        }
+        if let Some(date_time) = notification.date_time() {
+          write!(f, ", {}", date_time)?;
+        }
+        write!(f, ")")
+      },
+      Self::EventNotification(notification) => {
+        write!(f, "EventNotification(")?;
+        attribute(f, notification.attribute_descriptor())?;
+        if let Some(time) = notification.time() {
+          write!(f, ", {}", time)?;
+        }
+        write!(f, ")")
+      },
+      Self::GetRequest(request) => {
+        write!(f, "GetRequest(invoke_id={}, ", request.invoke_id_and_priority().invoke_id())?;
+        attribute(f, request.attribute_descriptor())?;
+        write!(f, ")")
+      },
+      Self::GetResponse(response) => {
+        write!(f, "GetResponse(invoke_id={}", response.invoke_id_and_priority().invoke_id())?;
+        if let Err(result) = response.result() {
+          write!(f, ", {:?}", result)?;
+        }
+        write!(f, ")")
+      },
+      Self::SetRequest(request) => {
+        write!(f, "SetRequest(invoke_id={}, ", request.invoke_id_and_priority().invoke_id())?;
+        attribute(f, request.attribute_descriptor())?;
+        write!(f, ")")
+      },
+      Self::SetResponse(response) => {
+        write!(f, "SetResponse(invoke_id={}, {:?})", response.invoke_id_and_priority().invoke_id(), response.result())
+      },
+      Self::ActionRequest(request) => {
+        let method = request.method_descriptor();
+        write!(
+          f,
+          "ActionRequest(invoke_id={}, method={}/{}/{})",
+          request.invoke_id_and_priority().invoke_id(),
+          method.class_id(),
+          method.instance_id(),
+          method.method_id()
+        )
+      },
+      Self::ActionResponse(response) => write!(
+        f,
+        "ActionResponse(invoke_id={}, {:?})",
+        response.invoke_id_and_priority().invoke_id(),
+        response.result()
+      ),
+      Self::GeneralGloCiphering(ciphering) => {
+        write!(f, "GeneralGloCiphering(")?;
+        if let Some(system_title) = ciphering.system_title() {
+          write!(f, "system_title=")?;
+          // System titles usually start with the three-letter manufacturer ID.
+          if system_title[..3].iter().all(u8::is_ascii_uppercase) {
+            for &c in &system_title[..3] {
+              write!(f, "{}", c as char)?;
+            }
+            for b in &system_title[3..] {
+              write!(f, "{:02x}", b)?;
+            }
+          } else {
+            for b in system_title {
+              write!(f, "{:02x}", b)?;
+            }
+          }
+          write!(f, ", ")?;
+        }
+        write!(f, "{}", if ciphering.is_encrypted() { "encrypted" } else { "unencrypted" })?;
+        if let Some(invocation_counter) = ciphering.invocation_counter() {
+          write!(f, ", counter={}", invocation_counter)?;
+        }
+        write!(f, ")")
+      },
+    }
+  }
+}
@@ -0,0 +1,47 @@
+//! OBIS codes of commonly used registers.
+
+use crate::ObisCode;
+
+/// Clock.
+pub const CLOCK: ObisCode = ObisCode::new(0, 0, 1, 0, 0, 255);
+/// COSEM logical device name.
+pub const LOGICAL_DEVICE_NAME: ObisCode = ObisCode::new(0, 0, 42, 0, 0, 255);
+/// Meter serial number.
+pub const METER_SERIAL_NUMBER: ObisCode = ObisCode::new(0, 0, 96, 1, 0, 255);
+
+/// Positive active energy (A+) total.
+pub const ACTIVE_ENERGY_IMPORT: ObisCode = ObisCode::new(1, 0, 1, 8, 0, 255);
+/// Negative active energy (A-) total.
+pub const ACTIVE_ENERGY_EXPORT: ObisCode = ObisCode::new(1, 0, 2, 8, 0, 255);
+/// Positive reactive energy (Q+) total.
+pub const REACTIVE_ENERGY_IMPORT: ObisCode = ObisCode::new(1, 0, 3, 8, 0, 255);
+/// Negative reactive energy (Q-) total.
+pub const REACTIVE_ENERGY_EXPORT: ObisCode = ObisCode::new(1, 0, 4, 8, 0, 255);
+
+/// Positive active instantaneous power (A+).
+pub const ACTIVE_POWER_IMPORT: ObisCode = ObisCode::new(1, 0, 1, 7, 0, 255);
+/// Negative active instantaneous power (A-).
+pub const ACTIVE_POWER_EXPORT: ObisCode = ObisCode::new(1, 0, 2, 7, 0, 255);
+/// Positive reactive instantaneous power (Q+).
+pub const REACTIVE_POWER_IMPORT: ObisCode = ObisCode::new(1, 0, 3, 7, 0, 255);
+/// Negative reactive instantaneous power (Q-).
+pub const REACTIVE_POWER_EXPORT: ObisCode = ObisCode::new(1, 0, 4, 7, 0, 255);
+
+/// Instantaneous current in phase L1.
+pub const CURRENT_L1: ObisCode = ObisCode::new(1, 0, 31, 7, 0, 255);
+/// Instantaneous current in phase L2.
+pub const CURRENT_L2: ObisCode = ObisCode::new(1, 0, 51, 7, 0, 255);
+/// Instantaneous current in phase L3.
+pub const CURRENT_L3: ObisCode = ObisCode::new(1, 0, 71, 7, 0, 255);
+
+/// Instantaneous voltage in phase L1.
+pub const VOLTAGE_L1: ObisCode = ObisCode::new(1, 0, 32, 7, 0, 255);
+/// Instantaneous voltage in phase L2.
+pub const VOLTAGE_L2: ObisCode = ObisCode::new(1, 0, 52, 7, 0, 255);
+/// Instantaneous voltage in phase L3.
+pub const VOLTAGE_L3: ObisCode = ObisCode::new(1, 0, 72, 7, 0, 255);
+
+/// Instantaneous power factor.
+pub const POWER_FACTOR: ObisCode = ObisCode::new(1, 0, 13, 7, 0, 255);
+/// Supply frequency.
+pub const FREQUENCY: ObisCode = ObisCode::new(1, 0, 14, 7, 0, 255);
@@ -6,24 +6,18 @@ pub enum HeaderType {
   Long,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Direction {
-  MasterSlave,
-  SlaveMaster,
-}
-
 /// M-Bus control information.
 #[derive(Debug, Clone)]
 pub enum ControlInformation {
   Segmented { segment: u8, last_segment: bool },
-  Unsegmented { header: HeaderType, direction: Direction },
+  Unsegmented { header: HeaderType },
 }
 
 impl TryFrom<u8> for ControlInformation {
   type Error = u8;
 
   fn try_from(control_information: u8) -> Result<Self, Self::Error> {
-    use {Direction::*, HeaderType::*};
+    use HeaderType::*;
 
     Ok(match control_information {
       0x00..=0x1f => {
@@ -32,10 +26,13 @@ impl TryFrom<u8> for ControlInformation {
 
         Self::Segmented { segment, last_segment }
       },
-      0x60 => Self::Unsegmented { header: Long, direction: MasterSlave },
-      0x61 => Self::Unsegmented { header: Short, direction: MasterSlave },
-      0x7c => Self::Unsegmented { header: Long, direction: SlaveMaster },
-      0x7d => Self::Unsegmented { header: Short, direction: SlaveMaster },
+      0x60 => Self::Unsegmented { header: Long },
+      0x61 => Self::Unsegmented { header: Short },
+      // Generic application layer headers, as used e.g. by wireless M-Bus.
+      0x72 => Self::Unsegmented { header: Long },
+      0x7a => Self::Unsegmented { header: Short },
+      0x7c => Self::Unsegmented { header: Long },
+      0x7d => Self::Unsegmented { header: Short },
       _ => return Err(control_information),
     })
   }
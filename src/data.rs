@@ -1,20 +1,34 @@
 #[cfg(feature = "serde")]
 use alloc::string::ToString;
-use alloc::{string::String, vec::Vec};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, string::String, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::cmp::Ordering;
 use core::convert::TryFrom;
 use core::fmt;
+use core::mem;
 
 use nom::{
+  bytes::streaming::take,
   combinator::fail,
-  multi::length_count,
-  number::streaming::{be_f32, be_f64, be_i16, be_i32, be_i64, be_u16, be_u32, be_u64, i8, u8},
+  number::streaming::{be_i16, be_u16, u8},
   sequence::tuple,
   IResult,
 };
+#[cfg(feature = "alloc")]
+use nom::{combinator::complete, multi::length_data};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Serializer};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg(feature = "alloc")]
+use crate::DataRef;
+
+/// A DLMS data type.
+///
+/// The discriminant of each variant is the tag which identifies the type on the
+/// wire, i.e. `DataType::OctetString as u8 == 9`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
 #[non_exhaustive]
 #[rustfmt::skip]
 pub enum DataType {
@@ -42,6 +56,73 @@ pub enum DataType {
   DateTime           = 25,
   Date               = 26,
   Time               = 27,
+  /// A signed 8-bit difference to the previous value, used in compact arrays.
+  DeltaInteger       = 28,
+  /// A signed 16-bit difference to the previous value, used in compact arrays.
+  DeltaLong          = 29,
+  /// A signed 32-bit difference to the previous value, used in compact arrays.
+  DeltaDoubleLong    = 30,
+  /// An unsigned 8-bit difference to the previous value, used in compact arrays.
+  DeltaUnsigned      = 31,
+  /// An unsigned 16-bit difference to the previous value, used in compact arrays.
+  DeltaLongUnsigned  = 32,
+  /// An unsigned 32-bit difference to the previous value, used in compact arrays.
+  DeltaDoubleLongUnsigned = 33,
+}
+
+impl DataType {
+  /// Get the data type for the given wire tag.
+  ///
+  /// ```
+  /// use dlms_cosem::DataType;
+  ///
+  /// assert_eq!(DataType::from_tag(0x09), Some(DataType::OctetString));
+  /// assert_eq!(DataType::OctetString.to_tag(), 0x09);
+  /// ```
+  pub fn from_tag(tag: u8) -> Option<Self> {
+    Self::try_from(tag).ok()
+  }
+
+  /// Get the wire tag of this data type.
+  pub fn to_tag(&self) -> u8 {
+    *self as u8
+  }
+
+  /// All known data types, ordered by their tag.
+  pub fn all() -> &'static [Self] {
+    &[
+      Self::Null,
+      Self::Array,
+      Self::Structure,
+      Self::Bool,
+      Self::BitString,
+      Self::DoubleLong,
+      Self::DoubleLongUnsigned,
+      Self::OctetString,
+      Self::VisibleString,
+      Self::Utf8String,
+      Self::BinaryCodedDecimal,
+      Self::Integer,
+      Self::Long,
+      Self::Unsigned,
+      Self::LongUnsigned,
+      Self::CompactArray,
+      Self::Long64,
+      Self::Long64Unsigned,
+      Self::Enum,
+      Self::Float32,
+      Self::Float64,
+      Self::DateTime,
+      Self::Date,
+      Self::Time,
+      Self::DeltaInteger,
+      Self::DeltaLong,
+      Self::DeltaDoubleLong,
+      Self::DeltaUnsigned,
+      Self::DeltaLongUnsigned,
+      Self::DeltaDoubleLongUnsigned,
+    ]
+  }
 }
 
 impl TryFrom<u8> for DataType {
@@ -73,12 +154,49 @@ impl TryFrom<u8> for DataType {
       0x19 => Self::DateTime,
       0x1a => Self::Date,
       0x1b => Self::Time,
+      0x1c => Self::DeltaInteger,
+      0x1d => Self::DeltaLong,
+      0x1e => Self::DeltaDoubleLong,
+      0x1f => Self::DeltaUnsigned,
+      0x20 => Self::DeltaLongUnsigned,
+      0x21 => Self::DeltaDoubleLongUnsigned,
       dt => return Err(dt),
     })
   }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+/// Parse an A-XDR length, which is either a single byte or, if the most
+/// significant bit is set, `0x80 | n` followed by an `n`-byte big-endian length.
+pub(crate) fn parse_length(input: &[u8]) -> IResult<&[u8], usize> {
+  let (input, len) = u8(input)?;
+  if len & 0x80 == 0 {
+    return Ok((input, len as usize))
+  }
+
+  let (input, bytes) = take(len & 0x7f)(input)?;
+  if bytes.is_empty() || bytes.len() > mem::size_of::<usize>() {
+    return fail(input)
+  }
+
+  Ok((input, bytes.iter().fold(0, |len, &b| (len << 8) | b as usize)))
+}
+
+#[cfg(feature = "alloc")]
+/// Parse an A-XDR `OPTIONAL` value, which is prefixed with a byte indicating its presence.
+pub(crate) fn parse_optional<'i, O>(
+  mut f: impl FnMut(&'i [u8]) -> IResult<&'i [u8], O>,
+) -> impl FnMut(&'i [u8]) -> IResult<&'i [u8], Option<O>> {
+  move |input| match u8(input)? {
+    (input, 0x00) => Ok((input, None)),
+    (input, 0x01) => {
+      let (input, value) = f(input)?;
+      Ok((input, Some(value)))
+    },
+    (input, _) => fail(input),
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Date {
   pub(crate) year: u16,
   pub(crate) month: u8,
@@ -87,7 +205,7 @@ pub struct Date {
 }
 
 impl Date {
-  fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+  pub(crate) fn parse(input: &[u8]) -> IResult<&[u8], Self> {
     let (input, year) = be_u16(input)?;
     let (input, month) = u8(input)?;
     let (input, day_of_month) = u8(input)?;
@@ -119,7 +237,12 @@ impl Serialize for Date {
   }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+/// A time of day.
+///
+/// Each field may be transmitted as `0xff` if it is not specified, in which case it is `None`.
+/// Any other value outside of the field's range, including other special values such as `0xfe`
+/// or `0xfd`, is rejected when parsing.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Time {
   pub(crate) hour: Option<u8>,
   pub(crate) minute: Option<u8>,
@@ -128,6 +251,39 @@ pub struct Time {
 }
 
 impl Time {
+  pub fn hour(&self) -> Option<u8> {
+    self.hour
+  }
+
+  pub fn minute(&self) -> Option<u8> {
+    self.minute
+  }
+
+  pub fn second(&self) -> Option<u8> {
+    self.second
+  }
+
+  pub fn hundredth(&self) -> Option<u8> {
+    self.hundredth
+  }
+
+  /// Whether some, but not all, fields are specified, e.g. a time with hours and minutes but no seconds.
+  ///
+  /// ```
+  /// use dlms_cosem::Time;
+  ///
+  /// let (_, time) = Time::parse(&[0x13, 0x0d, 0xff, 0xff]).unwrap();
+  /// assert!(time.is_partially_specified());
+  /// assert_eq!(time.second(), None);
+  ///
+  /// let (_, time) = Time::parse(&[0x13, 0x0d, 0x19, 0x00]).unwrap();
+  /// assert!(!time.is_partially_specified());
+  /// ```
+  pub fn is_partially_specified(&self) -> bool {
+    let fields = [self.hour, self.minute, self.second, self.hundredth];
+    fields.iter().any(Option::is_some) && fields.iter().any(Option::is_none)
+  }
+
   pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
     let (input, (hour, minute, second, hundredth)) = tuple((u8, u8, u8, u8))(input)?;
 
@@ -185,7 +341,7 @@ impl Serialize for Time {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct ClockStatus(pub(crate) u8);
 
@@ -222,7 +378,7 @@ impl ClockStatus {
   }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct DateTime {
   pub(crate) date: Date,
   pub(crate) time: Time,
@@ -231,6 +387,14 @@ pub struct DateTime {
 }
 
 impl DateTime {
+  /// The length of an encoded date-time in bytes.
+  pub const LEN: usize = 12;
+
+  /// Parse the fixed-length encoding of a date-time, without a type tag or length prefix.
+  ///
+  /// A date-time appears in this form after the `date-time` type tag (`0x19`) in [`Data`], which is
+  /// handled by [`Data::parse`]. Meters also commonly send date-times, e.g. the header of a data notification
+  /// or the value of a clock register, as an octet string of [`LEN`](Self::LEN) bytes, see [`Data::as_date_time`].
   pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
     let (input, date) = Date::parse(input)?;
     let (input, time) = Time::parse(input)?;
@@ -241,6 +405,41 @@ impl DateTime {
 
     Ok((input, Self { date, time, offset_minutes, clock_status }))
   }
+
+  #[cfg(feature = "alloc")]
+  /// Compare two points in time, if both are fully specified and have the same offset.
+  pub(crate) fn partial_cmp_instant(&self, other: &Self) -> Option<Ordering> {
+    if self.offset_minutes != other.offset_minutes {
+      return None
+    }
+
+    let key = |dt: &Self| {
+      let Date { year, month, day_of_month, .. } = dt.date;
+      if year == 0xffff || month > 12 || day_of_month > 31 {
+        return None
+      }
+
+      let Time { hour, minute, second, hundredth } = dt.time;
+      Some((year, month, day_of_month, hour?, minute?, second?, hundredth.unwrap_or(0)))
+    };
+
+    Some(key(self)?.cmp(&key(other)?))
+  }
+
+  #[cfg(feature = "alloc")]
+  /// Parse a date-time encoded as a length-prefixed octet string, where an empty
+  /// octet string means that no date-time is present.
+  pub(crate) fn parse_octet_string(input: &[u8]) -> IResult<&[u8], Option<Self>> {
+    let (input, date_time) = length_data(parse_length)(input)?;
+    match date_time.len() {
+      0 => Ok((input, None)),
+      Self::LEN => {
+        let (_, date_time) = complete(Self::parse)(date_time)?;
+        Ok((input, Some(date_time)))
+      },
+      _ => fail(date_time),
+    }
+  }
 }
 
 impl fmt::Display for DateTime {
@@ -277,6 +476,7 @@ impl Serialize for DateTime {
   }
 }
 
+#[cfg(feature = "alloc")]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "serde", serde(untagged))]
 #[derive(Debug, Clone, PartialEq)]
@@ -284,6 +484,7 @@ impl Serialize for DateTime {
 pub enum Data {
   Null,
   OctetString(Vec<u8>),
+  VisibleString(String),
   Utf8String(String),
   Integer(i8),
   Unsigned(u8),
@@ -298,78 +499,730 @@ pub enum Data {
   DateTime(DateTime),
   Date(Date),
   Time(Time),
+  Array(Vec<Data>),
   Structure(Vec<Data>),
   Enum(u8),
+  /// Two packed binary-coded decimal digits, most significant first.
+  ///
+  /// Serialized as an integer if both digits are valid, otherwise as its byte.
+  #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_bcd"))]
+  Bcd(u8),
+  Bool(bool),
+  BitString(BitString),
+  /// A signed difference to the previous value, used in compact arrays, see [`Data::accumulate_deltas`].
+  DeltaInteger(i8),
+  /// A signed difference to the previous value, used in compact arrays, see [`Data::accumulate_deltas`].
+  DeltaLong(i16),
+  /// A signed difference to the previous value, used in compact arrays, see [`Data::accumulate_deltas`].
+  DeltaDoubleLong(i32),
+  /// An unsigned difference to the previous value, used in compact arrays, see [`Data::accumulate_deltas`].
+  DeltaUnsigned(u8),
+  /// An unsigned difference to the previous value, used in compact arrays, see [`Data::accumulate_deltas`].
+  DeltaLongUnsigned(u16),
+  /// An unsigned difference to the previous value, used in compact arrays, see [`Data::accumulate_deltas`].
+  DeltaDoubleLongUnsigned(u32),
 }
 
-impl Data {
+#[cfg(feature = "serde")]
+fn serialize_bcd<S>(byte: &u8, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: Serializer,
+{
+  let bytes = core::slice::from_ref(byte);
+  match decode_bcd(bytes) {
+    Some(n) => serializer.serialize_u64(n),
+    None => bytes.serialize(serializer),
+  }
+}
+
+#[cfg(feature = "alloc")]
+fn decode_bcd(bytes: &[u8]) -> Option<u64> {
+  bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).try_fold(0u64, |n, digit| {
+    if digit > 9 {
+      return None
+    }
+
+    n.checked_mul(10)?.checked_add(digit as u64)
+  })
+}
+
+/// A sequence of bits, most significant bit of the first byte first.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitString {
+  pub(crate) len: usize,
+  pub(crate) bytes: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl BitString {
+  /// The number of bits.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// The bytes containing the bits, including any unused bits at the end of the last byte.
+  pub fn bytes(&self) -> &[u8] {
+    &self.bytes
+  }
+
+  /// Get the bit at `index`, counting from the most significant bit of the first byte.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let (_, data) = Data::parse(&[0x04, 0x0a, 0b10100000, 0b01000000]).unwrap();
+  /// let bits = match data {
+  ///   Data::BitString(bits) => bits,
+  ///   data => panic!("unexpected data: {:?}", data),
+  /// };
+  /// assert_eq!(bits.len(), 10);
+  /// assert_eq!(bits.get(0), Some(true));
+  /// assert_eq!(bits.get(1), Some(false));
+  /// assert_eq!(bits.get(9), Some(true));
+  /// assert_eq!(bits.get(10), None);
+  /// ```
+  pub fn get(&self, index: usize) -> Option<bool> {
+    if index >= self.len {
+      return None
+    }
+
+    Some(self.bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for BitString {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    use core::fmt::Write;
+    use serde::ser::SerializeStruct;
+
+    let bits = (0..self.len).map(|i| if self.get(i) == Some(true) { '1' } else { '0' }).collect::<String>();
+    let mut hex = String::with_capacity(self.bytes.len() * 2);
+    for b in &self.bytes {
+      write!(hex, "{:02x}", b).map_err(serde::ser::Error::custom)?;
+    }
+
+    let mut s = serializer.serialize_struct("BitString", 2)?;
+    s.serialize_field("bits", &bits)?;
+    s.serialize_field("hex", &hex)?;
+    s.end()
+  }
+}
+
+/// How deeply arrays and structures may be nested, bounding the recursion when parsing untrusted input.
+#[cfg(feature = "alloc")]
+const MAX_NESTING: usize = 32;
+
+/// The type of the elements of a compact array.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypeDescription {
+  Scalar(DataType),
+  Structure(Vec<TypeDescription>),
+  /// An array with the given element type and number of elements.
+  Array(Box<TypeDescription>, usize),
+}
+
+#[cfg(feature = "alloc")]
+impl TypeDescription {
   pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    Self::parse_nested(input, MAX_NESTING)
+  }
+
+  fn parse_nested(input: &[u8], depth: usize) -> IResult<&[u8], Self> {
     let (input, data_type) = u8(input)?;
-    let data_type = DataType::try_from(data_type)
-      .map_err(|_| nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Fail)))?;
-    Ok(match data_type {
-      DataType::DateTime => {
-        let (input, date_time) = DateTime::parse(input)?;
-        (input, Data::DateTime(date_time))
-      },
-      DataType::Date => {
-        let (input, date) = Date::parse(input)?;
-        (input, Data::Date(date))
-      },
-      DataType::Time => {
-        let (input, time) = Time::parse(input)?;
-        (input, Data::Time(time))
-      },
-      DataType::Null => (input, Data::Null),
-      DataType::Structure => {
-        let (input, structure) = length_count(u8, Self::parse)(input)?;
-        (input, Data::Structure(structure))
-      },
-      DataType::OctetString => {
-        let (input, bytes) = length_count(u8, u8)(input)?;
-        (input, Data::OctetString(bytes))
-      },
-      DataType::Float32 => {
-        let (input, n) = be_f32(input)?;
-        (input, Data::Float32(n))
-      },
-      DataType::Float64 => {
-        let (input, n) = be_f64(input)?;
-        (input, Data::Float64(n))
+    let depth = match depth.checked_sub(1) {
+      Some(depth) => depth,
+      None => return fail(input),
+    };
+
+    match DataType::try_from(data_type) {
+      Ok(DataType::Array) => {
+        let (input, len) = be_u16(input)?;
+        let (input, element) = Self::parse_nested(input, depth)?;
+        Ok((input, Self::Array(Box::new(element), len as usize)))
       },
-      DataType::Integer => {
-        let (input, n) = i8(input)?;
-        (input, Data::Integer(n))
+      Ok(DataType::Structure) => {
+        let (mut input, len) = parse_length(input)?;
+
+        let mut elements = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+          let (rest, element) = Self::parse_nested(input, depth)?;
+          elements.push(element);
+          input = rest;
+        }
+
+        Ok((input, Self::Structure(elements)))
       },
-      DataType::Long => {
-        let (input, n) = be_i16(input)?;
-        (input, Data::Long(n))
+      Ok(DataType::CompactArray) | Err(_) => fail(input),
+      Ok(data_type) => Ok((input, Self::Scalar(data_type))),
+    }
+  }
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_from {
+  ($($ty:ty => $variant:ident,)*) => {
+    $(
+      impl From<$ty> for Data {
+        fn from(value: $ty) -> Self {
+          Self::$variant(value)
+        }
+      }
+    )*
+  };
+}
+
+#[cfg(feature = "alloc")]
+impl_from! {
+  i8 => Integer,
+  u8 => Unsigned,
+  i16 => Long,
+  u16 => LongUnsigned,
+  i32 => DoubleLong,
+  u32 => DoubleLongUnsigned,
+  i64 => Long64,
+  u64 => Long64Unsigned,
+  f32 => Float32,
+  f64 => Float64,
+  String => Utf8String,
+  DateTime => DateTime,
+  Date => Date,
+  Time => Time,
+  Vec<Data> => Structure,
+}
+
+#[cfg(feature = "alloc")]
+impl From<&str> for Data {
+  fn from(value: &str) -> Self {
+    Self::Utf8String(value.into())
+  }
+}
+
+#[cfg(feature = "alloc")]
+impl From<DataRef<'_>> for Data {
+  /// Convert a borrowed data item, where arrays and structures are converted without their items.
+  fn from(data: DataRef<'_>) -> Self {
+    match data {
+      DataRef::Null => Self::Null,
+      DataRef::OctetString(bytes) => Self::OctetString(bytes.to_vec()),
+      DataRef::VisibleString(s) => Self::VisibleString(s.into()),
+      DataRef::Utf8String(s) => Self::Utf8String(s.into()),
+      DataRef::Integer(n) => Self::Integer(n),
+      DataRef::Unsigned(n) => Self::Unsigned(n),
+      DataRef::Long(n) => Self::Long(n),
+      DataRef::LongUnsigned(n) => Self::LongUnsigned(n),
+      DataRef::DoubleLong(n) => Self::DoubleLong(n),
+      DataRef::DoubleLongUnsigned(n) => Self::DoubleLongUnsigned(n),
+      DataRef::Long64(n) => Self::Long64(n),
+      DataRef::Long64Unsigned(n) => Self::Long64Unsigned(n),
+      DataRef::Float32(n) => Self::Float32(n),
+      DataRef::Float64(n) => Self::Float64(n),
+      DataRef::DateTime(date_time) => Self::DateTime(date_time),
+      DataRef::Date(date) => Self::Date(date),
+      DataRef::Time(time) => Self::Time(time),
+      DataRef::Array(_) => Self::Array(Vec::new()),
+      DataRef::Structure(_) => Self::Structure(Vec::new()),
+      DataRef::Enum(n) => Self::Enum(n),
+      DataRef::Bcd(n) => Self::Bcd(n),
+      DataRef::Bool(b) => Self::Bool(b),
+      DataRef::BitString(len, bytes) => Self::BitString(BitString { len, bytes: bytes.to_vec() }),
+      DataRef::DeltaInteger(n) => Self::DeltaInteger(n),
+      DataRef::DeltaLong(n) => Self::DeltaLong(n),
+      DataRef::DeltaDoubleLong(n) => Self::DeltaDoubleLong(n),
+      DataRef::DeltaUnsigned(n) => Self::DeltaUnsigned(n),
+      DataRef::DeltaLongUnsigned(n) => Self::DeltaLongUnsigned(n),
+      DataRef::DeltaDoubleLongUnsigned(n) => Self::DeltaDoubleLongUnsigned(n),
+    }
+  }
+}
+
+#[cfg(feature = "alloc")]
+impl Data {
+  /// Parse a sequence of `len` data items.
+  ///
+  /// In contrast to `length_count`, this reserves space for all items up front.
+  /// Since every item is at least one byte long, `len` cannot cause
+  /// more than `input.len()` items to be reserved.
+  fn parse_sequence(mut input: &[u8], len: usize, depth: usize) -> IResult<&[u8], Vec<Self>> {
+    let mut items = Vec::with_capacity(len.min(input.len()));
+    for _ in 0..len {
+      let (rest, item) = Self::parse_nested(input, depth)?;
+      items.push(item);
+      input = rest;
+    }
+
+    Ok((input, items))
+  }
+
+  /// Create an octet string from the given bytes.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let data = Data::Structure(vec![Data::octet_string([1, 0, 1, 8, 0, 255]), 5308437u32.into()]);
+  /// assert_eq!(data, Data::Structure(vec![Data::OctetString(vec![1, 0, 1, 8, 0, 255]), Data::DoubleLongUnsigned(5308437)]));
+  /// ```
+  pub fn octet_string(bytes: impl AsRef<[u8]>) -> Self {
+    Self::OctetString(bytes.as_ref().to_vec())
+  }
+
+  /// The number of items in an array or structure, or the number of bytes in a string.
+  ///
+  /// Returns `None` for scalar variants.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// assert_eq!(Data::Structure(vec![Data::Null, Data::Enum(30)]).len(), Some(2));
+  /// assert_eq!(Data::octet_string([1, 0, 1, 8, 0, 255]).len(), Some(6));
+  /// assert_eq!(Data::Integer(42).len(), None);
+  /// ```
+  pub fn len(&self) -> Option<usize> {
+    match self {
+      Self::Array(items) | Self::Structure(items) => Some(items.len()),
+      Self::OctetString(bytes) => Some(bytes.len()),
+      Self::VisibleString(s) | Self::Utf8String(s) => Some(s.len()),
+      _ => None,
+    }
+  }
+
+  /// Whether an array, structure or string is empty.
+  ///
+  /// Returns `false` for scalar variants.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// assert!(Data::Structure(vec![]).is_empty());
+  /// assert!(!Data::octet_string([1]).is_empty());
+  /// assert!(!Data::Null.is_empty());
+  /// ```
+  pub fn is_empty(&self) -> bool {
+    self.len() == Some(0)
+  }
+
+  /// Get the item at `index` of an array or structure.
+  ///
+  /// Returns `None` if the index is out of bounds or for other variants.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let data = Data::Structure(vec![Data::octet_string([1, 0, 1, 8, 0, 255]), Data::DoubleLongUnsigned(1234)]);
+  /// assert_eq!(data.get(1), Some(&Data::DoubleLongUnsigned(1234)));
+  /// assert_eq!(data.get(2), None);
+  /// assert_eq!(Data::octet_string([1]).get(0), None);
+  /// ```
+  pub fn get(&self, index: usize) -> Option<&Data> {
+    match self {
+      Self::Array(items) | Self::Structure(items) => items.get(index),
+      _ => None,
+    }
+  }
+
+  /// Call `f` on this item and, depth-first, on all items nested within it.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let data = Data::Structure(vec![
+  ///   Data::octet_string([1, 0, 1, 8, 0, 255]),
+  ///   Data::Structure(vec![Data::Integer(-3), Data::Enum(30)]),
+  /// ]);
+  ///
+  /// let mut count = 0;
+  /// data.walk(|item| {
+  ///   if !matches!(item, Data::Structure(_)) {
+  ///     count += 1;
+  ///   }
+  /// });
+  /// assert_eq!(count, 3);
+  /// ```
+  pub fn walk(&self, mut f: impl FnMut(&Data)) {
+    self.walk_dyn(&mut f)
+  }
+
+  fn walk_dyn(&self, f: &mut dyn FnMut(&Data)) {
+    f(self);
+
+    if let Self::Array(items) | Self::Structure(items) = self {
+      for item in items {
+        item.walk_dyn(f);
+      }
+    }
+  }
+
+  /// The data type of this item, for branching on a closed set of types instead of matching `Data`.
+  ///
+  /// Compact arrays are decoded to, and therefore reported as, [`DataType::Array`].
+  ///
+  /// ```
+  /// use dlms_cosem::{Data, DataType};
+  ///
+  /// assert_eq!(Data::DoubleLongUnsigned(1234).kind(), DataType::DoubleLongUnsigned);
+  /// assert_eq!(Data::Bcd(0x42).kind(), DataType::BinaryCodedDecimal);
+  /// ```
+  pub fn kind(&self) -> DataType {
+    match self {
+      Self::Null => DataType::Null,
+      Self::OctetString(_) => DataType::OctetString,
+      Self::VisibleString(_) => DataType::VisibleString,
+      Self::Utf8String(_) => DataType::Utf8String,
+      Self::Integer(_) => DataType::Integer,
+      Self::Unsigned(_) => DataType::Unsigned,
+      Self::Long(_) => DataType::Long,
+      Self::LongUnsigned(_) => DataType::LongUnsigned,
+      Self::DoubleLong(_) => DataType::DoubleLong,
+      Self::DoubleLongUnsigned(_) => DataType::DoubleLongUnsigned,
+      Self::Long64(_) => DataType::Long64,
+      Self::Long64Unsigned(_) => DataType::Long64Unsigned,
+      Self::Float32(_) => DataType::Float32,
+      Self::Float64(_) => DataType::Float64,
+      Self::DateTime(_) => DataType::DateTime,
+      Self::Date(_) => DataType::Date,
+      Self::Time(_) => DataType::Time,
+      Self::Array(_) => DataType::Array,
+      Self::Structure(_) => DataType::Structure,
+      Self::Enum(_) => DataType::Enum,
+      Self::Bcd(_) => DataType::BinaryCodedDecimal,
+      Self::Bool(_) => DataType::Bool,
+      Self::BitString(_) => DataType::BitString,
+      Self::DeltaInteger(_) => DataType::DeltaInteger,
+      Self::DeltaLong(_) => DataType::DeltaLong,
+      Self::DeltaDoubleLong(_) => DataType::DeltaDoubleLong,
+      Self::DeltaUnsigned(_) => DataType::DeltaUnsigned,
+      Self::DeltaLongUnsigned(_) => DataType::DeltaLongUnsigned,
+      Self::DeltaDoubleLongUnsigned(_) => DataType::DeltaDoubleLongUnsigned,
+    }
+  }
+
+  /// Convert an integer value to an `i64`, returning `None` for other variants or if it does not fit.
+  pub fn as_i64(&self) -> Option<i64> {
+    i64::try_from(self.as_integer()?).ok()
+  }
+
+  /// Convert an integer value to a `u64`, returning `None` for other variants or if it does not fit.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// assert_eq!(Data::LongUnsigned(42).as_u64(), Some(42));
+  /// assert_eq!(Data::Integer(-1).as_u64(), None);
+  /// assert_eq!(Data::Float32(1.0).as_u64(), None);
+  /// ```
+  pub fn as_u64(&self) -> Option<u64> {
+    u64::try_from(self.as_integer()?).ok()
+  }
+
+  fn as_integer(&self) -> Option<i128> {
+    Some(match *self {
+      Self::Integer(n) => n.into(),
+      Self::Unsigned(n) => n.into(),
+      Self::Long(n) => n.into(),
+      Self::LongUnsigned(n) => n.into(),
+      Self::DoubleLong(n) => n.into(),
+      Self::DoubleLongUnsigned(n) => n.into(),
+      Self::Long64(n) => n.into(),
+      Self::Long64Unsigned(n) => n.into(),
+      _ => return None,
+    })
+  }
+
+  /// Convert a numeric value to an `f64`, returning `None` for non-numeric variants.
+  pub fn as_f64(&self) -> Option<f64> {
+    match *self {
+      Self::Float32(n) => Some(n.into()),
+      Self::Float64(n) => Some(n),
+      _ => self.as_integer().map(|n| n as f64),
+    }
+  }
+
+  /// Multiply a numeric value by `10^scaler`.
+  ///
+  /// Integers up to 16 bits are converted to [`Float32`](Self::Float32), larger ones to
+  /// [`Float64`](Self::Float64). Floating point and non-numeric values are returned unchanged,
+  /// as are values whose scaled result overflows or underflows the target type.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// assert_eq!(Data::LongUnsigned(1234).scaled(-1), Data::Float32(123.4));
+  /// assert_eq!(Data::DoubleLong(-5).scaled(3), Data::Float64(-5000.0));
+  /// assert_eq!(Data::LongUnsigned(5).scaled(40), Data::LongUnsigned(5));
+  /// assert_eq!(Data::LongUnsigned(5).scaled(-46), Data::LongUnsigned(5));
+  /// assert_eq!(Data::Float64(0.5).scaled(1), Data::Float64(0.5));
+  /// assert_eq!(Data::Null.scaled(1), Data::Null);
+  /// ```
+  pub fn scaled(&self, scaler: i8) -> Data {
+    macro_rules! scale {
+      ($value:expr, $variant:ident, $ty:ident) => {{
+        // `10^±128` is finite in `f64`, so only the conversion to the target type can
+        // overflow or underflow.
+        let factor = (0..scaler.unsigned_abs()).fold(1.0f64, |f, _| f * 10.0);
+        let scaled = if scaler < 0 { $value as f64 / factor } else { $value as f64 * factor } as $ty;
+
+        if scaled.is_finite() && (scaled != 0.0 || $value == 0) {
+          Self::$variant(scaled)
+        } else {
+          self.clone()
+        }
+      }};
+    }
+
+    match *self {
+      Self::Integer(n) => scale!(n, Float32, f32),
+      Self::Unsigned(n) => scale!(n, Float32, f32),
+      Self::Long(n) => scale!(n, Float32, f32),
+      Self::LongUnsigned(n) => scale!(n, Float32, f32),
+      Self::DoubleLong(n) => scale!(n, Float64, f64),
+      Self::DoubleLongUnsigned(n) => scale!(n, Float64, f64),
+      Self::Long64(n) => scale!(n, Float64, f64),
+      Self::Long64Unsigned(n) => scale!(n, Float64, f64),
+      ref value => value.clone(),
+    }
+  }
+
+  /// Compare two numeric values regardless of their variant.
+  ///
+  /// Integers are compared exactly, otherwise both values are converted to `f64`.
+  /// Returns `None` if either value is not numeric.
+  ///
+  /// ```
+  /// use core::cmp::Ordering;
+  ///
+  /// use dlms_cosem::Data;
+  ///
+  /// assert_eq!(Data::Long64Unsigned(u64::MAX).partial_cmp_numeric(&Data::Long64Unsigned(u64::MAX - 1)), Some(Ordering::Greater));
+  /// assert_eq!(Data::Integer(-1).partial_cmp_numeric(&Data::Float32(-0.5)), Some(Ordering::Less));
+  /// assert_eq!(Data::Null.partial_cmp_numeric(&Data::Integer(0)), None);
+  /// ```
+  pub fn partial_cmp_numeric(&self, other: &Data) -> Option<Ordering> {
+    match (self.as_integer(), other.as_integer()) {
+      (Some(a), Some(b)) => Some(a.cmp(&b)),
+      _ => self.as_f64()?.partial_cmp(&other.as_f64()?),
+    }
+  }
+
+  /// Compute the increase since `previous` of a counter which wraps around at its maximum value.
+  ///
+  /// Both values must be the same unsigned integer variant, otherwise `None` is returned.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let previous = Data::DoubleLongUnsigned(u32::MAX - 1);
+  /// assert_eq!(Data::DoubleLongUnsigned(3).wrapping_delta(&previous), Some(5));
+  /// assert_eq!(Data::LongUnsigned(3).wrapping_delta(&previous), None);
+  /// ```
+  pub fn wrapping_delta(&self, previous: &Data) -> Option<u64> {
+    Some(match (self, previous) {
+      (Self::Unsigned(current), Self::Unsigned(previous)) => current.wrapping_sub(*previous).into(),
+      (Self::LongUnsigned(current), Self::LongUnsigned(previous)) => current.wrapping_sub(*previous).into(),
+      (Self::DoubleLongUnsigned(current), Self::DoubleLongUnsigned(previous)) => current.wrapping_sub(*previous).into(),
+      (Self::Long64Unsigned(current), Self::Long64Unsigned(previous)) => current.wrapping_sub(*previous),
+      _ => return None,
+    })
+  }
+
+  /// Reconstruct absolute values from `deltas`, each being the difference to the previous value,
+  /// starting at this base value.
+  ///
+  /// Values of the delta data types in compact arrays are decoded as the delta variants, e.g.
+  /// [`Data::DeltaLongUnsigned`]. The returned values have the same variant as the base value.
+  /// Returns `None` if the base value is not an integer, any of `deltas` is not a delta or if the
+  /// result does not fit into the variant of the base value.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let base = Data::DoubleLongUnsigned(1000);
+  /// let deltas = [Data::DeltaUnsigned(5), Data::DeltaUnsigned(0), Data::DeltaUnsigned(12)];
+  /// assert_eq!(
+  ///   base.accumulate_deltas(&deltas),
+  ///   Some(vec![Data::DoubleLongUnsigned(1005), Data::DoubleLongUnsigned(1005), Data::DoubleLongUnsigned(1017)]),
+  /// );
+  /// ```
+  pub fn accumulate_deltas<'d>(&self, deltas: impl IntoIterator<Item = &'d Data>) -> Option<Vec<Data>> {
+    let mut value = self.as_integer()?;
+
+    deltas
+      .into_iter()
+      .map(|delta| {
+        value = value.checked_add(delta.as_delta()?)?;
+        self.with_integer(value)
+      })
+      .collect()
+  }
+
+  fn as_delta(&self) -> Option<i128> {
+    Some(match *self {
+      Self::DeltaInteger(n) => n.into(),
+      Self::DeltaLong(n) => n.into(),
+      Self::DeltaDoubleLong(n) => n.into(),
+      Self::DeltaUnsigned(n) => n.into(),
+      Self::DeltaLongUnsigned(n) => n.into(),
+      Self::DeltaDoubleLongUnsigned(n) => n.into(),
+      _ => return None,
+    })
+  }
+
+  /// Create an integer of the same variant as this one with the given value, if it fits.
+  fn with_integer(&self, n: i128) -> Option<Self> {
+    Some(match self {
+      Self::Integer(_) => Self::Integer(TryFrom::try_from(n).ok()?),
+      Self::Unsigned(_) => Self::Unsigned(TryFrom::try_from(n).ok()?),
+      Self::Long(_) => Self::Long(TryFrom::try_from(n).ok()?),
+      Self::LongUnsigned(_) => Self::LongUnsigned(TryFrom::try_from(n).ok()?),
+      Self::DoubleLong(_) => Self::DoubleLong(TryFrom::try_from(n).ok()?),
+      Self::DoubleLongUnsigned(_) => Self::DoubleLongUnsigned(TryFrom::try_from(n).ok()?),
+      Self::Long64(_) => Self::Long64(TryFrom::try_from(n).ok()?),
+      Self::Long64Unsigned(_) => Self::Long64Unsigned(TryFrom::try_from(n).ok()?),
+      _ => return None,
+    })
+  }
+
+  /// Interpret a date-time or an octet string of [`DateTime::LEN`] bytes as a date-time.
+  ///
+  /// Meters commonly transmit date-times, e.g. the value of a clock register, as octet strings.
+  /// Returns `None` for other variants or if the octet string is not a valid date-time.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// let data = Data::octet_string([0x07, 0xe5, 0x09, 0x08, 0x03, 0x13, 0x0d, 0x19, 0x00, 0x00, 0x00, 0x80]);
+  /// assert_eq!(data.as_date_time().unwrap().to_string(), "2021-09-08T19:13:25.00-00:00");
+  /// assert_eq!(Data::octet_string([1, 0, 1, 8, 0, 255]).as_date_time(), None);
+  /// ```
+  pub fn as_date_time(&self) -> Option<DateTime> {
+    match self {
+      Self::DateTime(date_time) => Some(*date_time),
+      Self::OctetString(bytes) if bytes.len() == DateTime::LEN => {
+        DateTime::parse(bytes).ok().map(|(_, date_time)| date_time)
       },
-      DataType::DoubleLong => {
-        let (input, n) = be_i32(input)?;
-        (input, Data::DoubleLong(n))
+      _ => None,
+    }
+  }
+
+  /// Decode packed BCD digits to an integer.
+  ///
+  /// Both [`Data::Bcd`] and [`Data::OctetString`] are accepted, since meters commonly
+  /// transmit multi-digit BCD values such as serial numbers as octet strings.
+  /// Returns `None` for other variants, invalid digits or if the value overflows.
+  ///
+  /// ```
+  /// use dlms_cosem::Data;
+  ///
+  /// assert_eq!(Data::OctetString(vec![0x12, 0x34, 0x56, 0x78]).as_bcd_u64(), Some(12345678));
+  /// assert_eq!(Data::Bcd(0x42).as_bcd_u64(), Some(42));
+  /// assert_eq!(Data::OctetString(vec![0x1a]).as_bcd_u64(), None);
+  /// ```
+  pub fn as_bcd_u64(&self) -> Option<u64> {
+    match self {
+      Self::Bcd(byte) => decode_bcd(core::slice::from_ref(byte)),
+      Self::OctetString(bytes) => decode_bcd(bytes),
+      _ => None,
+    }
+  }
+
+  /// Parse a value of the given type without a leading tag, as contained in a compact array.
+  fn parse_with_type<'i>(input: &'i [u8], type_description: &TypeDescription) -> IResult<&'i [u8], Self> {
+    match type_description {
+      TypeDescription::Scalar(data_type) => match DataRef::parse_value(*data_type, input)? {
+        (input, DataRef::Array(_) | DataRef::Structure(_)) => fail(input),
+        (input, data) => Ok((input, data.into())),
       },
-      DataType::Long64 => {
-        let (input, n) = be_i64(input)?;
-        (input, Data::Long64(n))
+      TypeDescription::Structure(types) => {
+        let mut input = input;
+        let mut items = Vec::with_capacity(types.len());
+        for type_description in types {
+          let (rest, item) = Self::parse_with_type(input, type_description)?;
+          items.push(item);
+          input = rest;
+        }
+
+        Ok((input, Self::Structure(items)))
       },
-      DataType::Enum => {
-        let (input, n) = u8(input)?;
-        (input, Data::Enum(n))
+      TypeDescription::Array(type_description, len) => {
+        let mut input = input;
+        let mut items = Vec::with_capacity((*len).min(input.len()));
+        for _ in 0..*len {
+          let (rest, item) = Self::parse_with_type(input, type_description)?;
+
+          // Bound the number of elements by the input length, like for the compact array itself.
+          if rest.len() == input.len() {
+            return fail(input)
+          }
+
+          items.push(item);
+          input = rest;
+        }
+
+        Ok((input, Self::Array(items)))
       },
-      DataType::LongUnsigned => {
-        let (input, n) = be_u16(input)?;
-        (input, Data::LongUnsigned(n))
+    }
+  }
+
+  /// Parse a compact array, i.e. a type description followed by the untagged values of all elements.
+  fn parse_compact_array(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, type_description) = TypeDescription::parse(input)?;
+    let (input, mut contents) = length_data(parse_length)(input)?;
+
+    let mut elements = Vec::new();
+    while !contents.is_empty() {
+      let (rest, element) = complete(|input| Self::parse_with_type(input, &type_description))(contents)?;
+
+      // Elements without content, e.g. of type `null-data`, would never end the array.
+      if rest.len() == contents.len() {
+        return fail(input)
+      }
+
+      elements.push(element);
+      contents = rest;
+    }
+
+    Ok((input, Self::Array(elements)))
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    Self::parse_nested(input, MAX_NESTING)
+  }
+
+  fn parse_nested(input: &[u8], depth: usize) -> IResult<&[u8], Self> {
+    if let Some((&tag, input)) = input.split_first() {
+      if tag == DataType::CompactArray.to_tag() {
+        return Self::parse_compact_array(input)
+      }
+    }
+
+    let (input, data) = DataRef::parse(input)?;
+    let depth = match data {
+      DataRef::Array(_) | DataRef::Structure(_) => match depth.checked_sub(1) {
+        Some(depth) => depth,
+        None => return fail(input),
       },
-      DataType::DoubleLongUnsigned => {
-        let (input, n) = be_u32(input)?;
-        (input, Data::DoubleLongUnsigned(n))
+      _ => depth,
+    };
+
+    Ok(match data {
+      DataRef::Array(len) => {
+        let (input, array) = Self::parse_sequence(input, len, depth)?;
+        (input, Data::Array(array))
       },
-      DataType::Long64Unsigned => {
-        let (input, n) = be_u64(input)?;
-        (input, Data::Long64Unsigned(n))
+      DataRef::Structure(len) => {
+        let (input, structure) = Self::parse_sequence(input, len, depth)?;
+        (input, Data::Structure(structure))
       },
-      dt => unimplemented!("decoding data type {:?}", dt),
+      data => (input, data.into()),
     })
   }
 }
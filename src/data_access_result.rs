@@ -0,0 +1,60 @@
+use core::convert::TryFrom;
+
+use derive_try_from_primitive::TryFromPrimitive;
+use nom::{combinator::fail, number::streaming::u8, IResult};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "alloc")]
+use crate::Data;
+
+/// The result of accessing an attribute.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[repr(u8)]
+#[non_exhaustive]
+#[rustfmt::skip]
+pub enum DataAccessResult {
+  Success                 =   0,
+  HardwareFault           =   1,
+  TemporaryFailure        =   2,
+  ReadWriteDenied         =   3,
+  ObjectUndefined         =   4,
+  ObjectClassInconsistent =   9,
+  ObjectUnavailable       =  11,
+  TypeUnmatched           =  12,
+  ScopeOfAccessViolated   =  13,
+  DataBlockUnavailable    =  14,
+  LongGetAborted          =  15,
+  NoLongGetInProgress     =  16,
+  LongSetAborted          =  17,
+  NoLongSetInProgress     =  18,
+  DataBlockNumberInvalid  =  19,
+  OtherReason             = 250,
+}
+
+impl DataAccessResult {
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, result) = u8(input)?;
+    match Self::try_from(result) {
+      Ok(result) => Ok((input, result)),
+      Err(_) => fail(input),
+    }
+  }
+}
+
+#[cfg(feature = "alloc")]
+/// Parse a `Get-Data-Result`, which is either the requested data or the reason why it could not be accessed.
+pub(crate) fn parse_get_data_result(input: &[u8]) -> IResult<&[u8], Result<Data, DataAccessResult>> {
+  match u8(input)? {
+    (input, 0) => {
+      let (input, data) = Data::parse(input)?;
+      Ok((input, Ok(data)))
+    },
+    (input, 1) => {
+      let (input, result) = DataAccessResult::parse(input)?;
+      Ok((input, Err(result)))
+    },
+    (input, _) => fail(input),
+  }
+}
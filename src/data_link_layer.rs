@@ -0,0 +1,78 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::Error;
+
+/// A data link layer which extracts APDUs from some transport, e.g. M-Bus or HDLC.
+///
+/// `I` is the input consumed by the layer, e.g. a byte slice or a slice of already parsed link layer frames.
+/// Implementing [`next_frame`](Self::next_frame) is sufficient, all other methods have default
+/// implementations based on it. Layers whose frames carry a header can additionally implement
+/// [`DlmsDataLinkLayerHeader`].
+///
+/// Implementations must consume exactly one complete APDU, which may have been reassembled from multiple
+/// link layer segments, and return the remaining input. If `input` ends before the APDU is complete,
+/// return [`Error::Incomplete`], counted in units of `I`, so the caller can retry with more input. Input
+/// which can never form a valid frame is reported as [`Error::InvalidFormat`], or [`Error::ChecksumMismatch`]
+/// if a link layer checksum is wrong. [`map_nom_error`](crate::map_nom_error) converts the result of a `nom`
+/// parser accordingly.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use dlms_cosem::{map_nom_error, Dlms, DlmsDataLinkLayer, Error};
+/// use nom::{multi::length_data, number::streaming::u8};
+///
+/// /// Frames prefixed with their length in a single byte.
+/// enum LengthPrefixed {}
+///
+/// impl<'i> DlmsDataLinkLayer<'i, &'i [u8]> for LengthPrefixed {
+///   fn next_frame(input: &'i [u8]) -> Result<(&'i [u8], Cow<'i, [u8]>), Error> {
+///     let (input, frame) = map_nom_error(length_data(u8)(input))?;
+///     Ok((input, Cow::Borrowed(frame)))
+///   }
+/// }
+///
+/// let bytes = [0x0b, 0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00, 0x04, 0xd2];
+/// let dlms = Dlms::new([0; 16]);
+///
+/// let (rest, _) = dlms.decrypt_apdu::<LengthPrefixed, _>(&bytes[..]).unwrap();
+/// assert!(rest.is_empty());
+///
+/// assert!(matches!(dlms.decrypt_apdu::<LengthPrefixed, _>(&bytes[..6]), Err(Error::Incomplete(_))));
+/// ```
+pub trait DlmsDataLinkLayer<'i, I> {
+  fn next_frame(input: I) -> Result<(I, Cow<'i, [u8]>), Error>;
+
+  /// Return the next frame together with the number of link layer segments it was reassembled from.
+  fn next_frame_with_segment_count(input: I) -> Result<(I, Cow<'i, [u8]>, usize), Error> {
+    let (input, frame) = Self::next_frame(input)?;
+    Ok((input, frame, 1))
+  }
+
+  /// Write the next frame into `buffer`, replacing its contents but reusing its allocation.
+  fn next_frame_into(input: I, buffer: &mut Vec<u8>) -> Result<I, Error> {
+    let (input, frame) = Self::next_frame(input)?;
+    buffer.clear();
+    buffer.extend_from_slice(&frame);
+    Ok(input)
+  }
+}
+
+/// A [`DlmsDataLinkLayer`] whose frames are accompanied by a link layer header.
+pub trait DlmsDataLinkLayerHeader<'i, I>: DlmsDataLinkLayer<'i, I> {
+  /// Link layer header accompanying each frame.
+  type Header;
+
+  /// Return the next frame together with its header.
+  #[allow(clippy::type_complexity)]
+  fn next_frame_with_header(input: I) -> Result<(I, Cow<'i, [u8]>, Self::Header), Error>;
+
+  /// The system title contained in `header`, if any.
+  ///
+  /// Used by [`Dlms::decrypt_with_header_system_title`](crate::Dlms::decrypt_with_header_system_title) for meters which only transmit
+  /// their system title in the link layer header.
+  fn system_title(_header: &Self::Header) -> Option<[u8; 8]> {
+    None
+  }
+}
@@ -1,8 +1,6 @@
-use nom::{
-  multi::length_value,
-  number::streaming::{be_u32, u8},
-  IResult,
-};
+use nom::{number::streaming::be_u32, IResult};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 use crate::{Data, DateTime};
 
@@ -16,18 +14,21 @@ impl LongInvokeIdAndPriority {
   }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Priority {
   Normal,
   High,
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ServiceClass {
   Confirmed,
   Unconfirmed,
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcessingOption {
   ContinueOnError,
@@ -68,10 +69,27 @@ impl LongInvokeIdAndPriority {
   }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for LongInvokeIdAndPriority {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let mut s = serializer.serialize_struct("LongInvokeIdAndPriority", 5)?;
+    s.serialize_field("invoke_id", &self.invoke_id())?;
+    s.serialize_field("priority", &self.priority())?;
+    s.serialize_field("service_class", &self.service_class())?;
+    s.serialize_field("processing_option", &self.processing_option())?;
+    s.serialize_field("self_descriptive", &self.self_descriptive())?;
+    s.end()
+  }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, PartialEq)]
 pub struct DataNotification {
   pub(crate) long_invoke_id_and_priority: LongInvokeIdAndPriority,
-  pub(crate) date_time: DateTime,
+  pub(crate) date_time: Option<DateTime>,
   pub(crate) notification_body: Data,
 }
 
@@ -96,9 +114,14 @@ impl DataNotification {
     self.long_invoke_id_and_priority.invoke_id()
   }
 
+  /// The time at which the notification was sent, if the meter included one.
+  pub fn date_time(&self) -> Option<&DateTime> {
+    self.date_time.as_ref()
+  }
+
   pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
     let (input, long_invoke_id_and_priority) = LongInvokeIdAndPriority::parse(input)?;
-    let (input, date_time) = length_value(u8, DateTime::parse)(input)?;
+    let (input, date_time) = DateTime::parse_octet_string(input)?;
     let (input, notification_body) = Data::parse(input)?;
     Ok((input, Self { long_invoke_id_and_priority, date_time, notification_body }))
   }
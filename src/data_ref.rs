@@ -0,0 +1,241 @@
+use core::convert::TryFrom;
+
+use nom::{
+  bytes::streaming::take,
+  combinator::fail,
+  multi::length_data,
+  number::streaming::{be_f32, be_f64, be_i16, be_i32, be_i64, be_u16, be_u32, be_u64, i8, u8},
+  IResult,
+};
+
+use crate::{data::parse_length, DataType, Date, DateTime, Time};
+
+/// A DLMS data item which borrows from the input instead of allocating.
+///
+/// Nested items are not contained in their parent, instead a [`DataRef::Array`] or
+/// [`DataRef::Structure`] only holds the number of items which follow it. Use
+/// [`DataRef::parse_into`] to parse a complete data item into a caller-provided
+/// buffer without an allocator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum DataRef<'i> {
+  Null,
+  OctetString(&'i [u8]),
+  VisibleString(&'i str),
+  Utf8String(&'i str),
+  Integer(i8),
+  Unsigned(u8),
+  Long(i16),
+  LongUnsigned(u16),
+  DoubleLong(i32),
+  DoubleLongUnsigned(u32),
+  Long64(i64),
+  Long64Unsigned(u64),
+  Float32(f32),
+  Float64(f64),
+  DateTime(DateTime),
+  Date(Date),
+  Time(Time),
+  /// The start of an array with the given number of items.
+  Array(usize),
+  /// The start of a structure with the given number of items.
+  Structure(usize),
+  Enum(u8),
+  /// Packed binary-coded decimal digits.
+  Bcd(u8),
+  Bool(bool),
+  /// A bit string with the given number of bits, most significant bit of the first byte first.
+  BitString(usize, &'i [u8]),
+  /// A signed difference to the previous value, used in compact arrays.
+  DeltaInteger(i8),
+  /// A signed difference to the previous value, used in compact arrays.
+  DeltaLong(i16),
+  /// A signed difference to the previous value, used in compact arrays.
+  DeltaDoubleLong(i32),
+  /// An unsigned difference to the previous value, used in compact arrays.
+  DeltaUnsigned(u8),
+  /// An unsigned difference to the previous value, used in compact arrays.
+  DeltaLongUnsigned(u16),
+  /// An unsigned difference to the previous value, used in compact arrays.
+  DeltaDoubleLongUnsigned(u32),
+}
+
+impl<'i> DataRef<'i> {
+  /// Parse a single data item.
+  ///
+  /// For a structure, only its header is parsed, i.e. the returned input starts with its first item.
+  pub fn parse(input: &'i [u8]) -> IResult<&'i [u8], Self> {
+    let (input, data_type) = u8(input)?;
+    let data_type = DataType::try_from(data_type)
+      .map_err(|_| nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Fail)))?;
+    Self::parse_value(data_type, input)
+  }
+
+  /// Parse a single data item of the given type without a leading tag.
+  pub(crate) fn parse_value(data_type: DataType, input: &'i [u8]) -> IResult<&'i [u8], Self> {
+    Ok(match data_type {
+      DataType::DateTime => {
+        let (input, date_time) = DateTime::parse(input)?;
+        (input, Self::DateTime(date_time))
+      },
+      DataType::Date => {
+        let (input, date) = Date::parse(input)?;
+        (input, Self::Date(date))
+      },
+      DataType::Time => {
+        let (input, time) = Time::parse(input)?;
+        (input, Self::Time(time))
+      },
+      DataType::Null => (input, Self::Null),
+      DataType::Array => {
+        let (input, len) = parse_length(input)?;
+        (input, Self::Array(len))
+      },
+      DataType::Structure => {
+        let (input, len) = parse_length(input)?;
+        (input, Self::Structure(len))
+      },
+      DataType::OctetString => {
+        let (input, bytes) = length_data(parse_length)(input)?;
+        (input, Self::OctetString(bytes))
+      },
+      DataType::Float32 => {
+        let (input, n) = be_f32(input)?;
+        (input, Self::Float32(n))
+      },
+      DataType::Float64 => {
+        let (input, n) = be_f64(input)?;
+        (input, Self::Float64(n))
+      },
+      DataType::Integer => {
+        let (input, n) = i8(input)?;
+        (input, Self::Integer(n))
+      },
+      DataType::DeltaInteger => {
+        let (input, n) = i8(input)?;
+        (input, Self::DeltaInteger(n))
+      },
+      DataType::Unsigned => {
+        let (input, n) = u8(input)?;
+        (input, Self::Unsigned(n))
+      },
+      DataType::DeltaUnsigned => {
+        let (input, n) = u8(input)?;
+        (input, Self::DeltaUnsigned(n))
+      },
+      DataType::Long => {
+        let (input, n) = be_i16(input)?;
+        (input, Self::Long(n))
+      },
+      DataType::DeltaLong => {
+        let (input, n) = be_i16(input)?;
+        (input, Self::DeltaLong(n))
+      },
+      DataType::DoubleLong => {
+        let (input, n) = be_i32(input)?;
+        (input, Self::DoubleLong(n))
+      },
+      DataType::DeltaDoubleLong => {
+        let (input, n) = be_i32(input)?;
+        (input, Self::DeltaDoubleLong(n))
+      },
+      DataType::Long64 => {
+        let (input, n) = be_i64(input)?;
+        (input, Self::Long64(n))
+      },
+      DataType::Bool => {
+        let (input, b) = u8(input)?;
+        (input, Self::Bool(b != 0))
+      },
+      DataType::BitString => {
+        let (input, len) = parse_length(input)?;
+        let (input, bytes) = take(len.div_ceil(8))(input)?;
+        (input, Self::BitString(len, bytes))
+      },
+      DataType::Enum => {
+        let (input, n) = u8(input)?;
+        (input, Self::Enum(n))
+      },
+      DataType::BinaryCodedDecimal => {
+        let (input, n) = u8(input)?;
+        (input, Self::Bcd(n))
+      },
+      DataType::LongUnsigned => {
+        let (input, n) = be_u16(input)?;
+        (input, Self::LongUnsigned(n))
+      },
+      DataType::DeltaLongUnsigned => {
+        let (input, n) = be_u16(input)?;
+        (input, Self::DeltaLongUnsigned(n))
+      },
+      DataType::DoubleLongUnsigned => {
+        let (input, n) = be_u32(input)?;
+        (input, Self::DoubleLongUnsigned(n))
+      },
+      DataType::DeltaDoubleLongUnsigned => {
+        let (input, n) = be_u32(input)?;
+        (input, Self::DeltaDoubleLongUnsigned(n))
+      },
+      DataType::Long64Unsigned => {
+        let (input, n) = be_u64(input)?;
+        (input, Self::Long64Unsigned(n))
+      },
+      DataType::VisibleString => {
+        let (rest, bytes) = length_data(parse_length)(input)?;
+        match core::str::from_utf8(bytes) {
+          Ok(s) if s.is_ascii() => (rest, Self::VisibleString(s)),
+          _ => return fail(input),
+        }
+      },
+      DataType::Utf8String => {
+        let (rest, bytes) = length_data(parse_length)(input)?;
+        match core::str::from_utf8(bytes) {
+          Ok(s) => (rest, Self::Utf8String(s)),
+          Err(_) => return fail(input),
+        }
+      },
+      // A compact array cannot be represented without allocating, see `Data::parse`.
+      DataType::CompactArray => return fail(input),
+    })
+  }
+
+  /// Parse a complete data item, including all nested items, into `buffer`.
+  ///
+  /// Items are stored in depth-first order, each array or structure being followed by its items.
+  /// Fails if `buffer` is too small to hold all items.
+  ///
+  /// ```
+  /// use dlms_cosem::DataRef;
+  ///
+  /// let input = [0x02, 0x02, 0x09, 0x02, 0x12, 0x34, 0x06, 0x00, 0x00, 0x00, 0x2a];
+  ///
+  /// let mut buffer = [DataRef::Null; 4];
+  /// let (_, items) = DataRef::parse_into(&input, &mut buffer).unwrap();
+  /// assert_eq!(items, [DataRef::Structure(2), DataRef::OctetString(&[0x12, 0x34]), DataRef::DoubleLongUnsigned(42)]);
+  ///
+  /// assert!(DataRef::parse_into(&input, &mut buffer[..2]).is_err());
+  /// ```
+  pub fn parse_into<'b>(mut input: &'i [u8], buffer: &'b mut [Self]) -> IResult<&'i [u8], &'b [Self]> {
+    let mut len = 0;
+    let mut remaining = 1;
+
+    while remaining > 0 {
+      let (rest, item) = Self::parse(input)?;
+      let slot = match buffer.get_mut(len) {
+        Some(slot) => slot,
+        None => return Err(nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::TooLarge))),
+      };
+
+      if let Self::Array(items) | Self::Structure(items) = item {
+        remaining += items;
+      }
+
+      *slot = item;
+      len += 1;
+      remaining -= 1;
+      input = rest;
+    }
+
+    Ok((input, &buffer[..len]))
+  }
+}
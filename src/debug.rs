@@ -0,0 +1,160 @@
+//! Helpers for debugging frames which cannot be decoded.
+
+use alloc::{string::String, vec};
+use core::fmt::Write;
+
+use nom::{number::complete::be_u32, IResult};
+
+use crate::{Apdu, Data, DataRef, DataType, DateTime};
+
+/// Render the parse tree of an unencrypted APDU with the offset of each item.
+///
+/// Parsing continues as far as possible and stops at the first error, which is annotated
+/// with its offset. The body of a data notification is dumped item by item, other APDUs are
+/// summarized in a single line.
+///
+/// ```
+/// use dlms_cosem::debug::dump;
+///
+/// #[rustfmt::skip]
+/// let bytes = [
+///   0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, // Data Notification
+///   0x02, 0x02, // Structure
+///     0x09, 0x06, 0x01, 0x00, 0x01, 0x08, 0x00, 0xff, // OBIS Code
+///     0x06, 0x00, 0x00, // Truncated Value
+/// ];
+///
+/// assert_eq!(
+///   dump(&bytes),
+///   "0000  0f DataNotification\n\
+///    0001    LongInvokeIdAndPriority(0x00000001)\n\
+///    0005    DateTime(None)\n\
+///    0006    02 Structure(2)\n\
+///    0008      09 OctetString([1, 0, 1, 8, 0, 255])\n\
+///    0010      error: incomplete\n"
+/// );
+/// ```
+pub fn dump(bytes: &[u8]) -> String {
+  let mut out = String::new();
+  let offset = |input: &[u8]| bytes.len() - input.len();
+
+  let rest = match bytes.first() {
+    Some(0x0f) => dump_data_notification(&mut out, bytes),
+    Some(_) => match Apdu::parse(bytes) {
+      Ok((rest, apdu)) => {
+        line(&mut out, 0, 0, format_args!("{:02x} {}", bytes[0], apdu));
+        Some(rest)
+      },
+      Err(err) => {
+        error(&mut out, bytes, bytes, 0, err);
+        None
+      },
+    },
+    None => {
+      line(&mut out, 0, 0, format_args!("error: empty input"));
+      None
+    },
+  };
+
+  if let Some(rest) = rest.filter(|rest| !rest.is_empty()) {
+    line(&mut out, offset(rest), 0, format_args!("{} trailing bytes", rest.len()));
+  }
+
+  out
+}
+
+/// Render the parse tree of a data item with the offset of each item.
+///
+/// Like [`dump`], but for a bare data item instead of an APDU.
+pub fn dump_data(bytes: &[u8]) -> String {
+  let mut out = String::new();
+
+  if let Some(rest) = data(&mut out, bytes, bytes, 0).filter(|rest| !rest.is_empty()) {
+    line(&mut out, bytes.len() - rest.len(), 0, format_args!("{} trailing bytes", rest.len()));
+  }
+
+  out
+}
+
+fn dump_data_notification<'i>(out: &mut String, bytes: &'i [u8]) -> Option<&'i [u8]> {
+  line(out, 0, 0, format_args!("{:02x} DataNotification", bytes[0]));
+  let input = &bytes[1..];
+
+  let (input, id) = step(out, bytes, input, 1, be_u32)?;
+  line(out, 1, 1, format_args!("LongInvokeIdAndPriority({:#010x})", id));
+
+  let (rest, date_time) = step(out, bytes, input, 1, DateTime::parse_octet_string)?;
+  match date_time {
+    Some(date_time) => line(out, bytes.len() - input.len(), 1, format_args!("DateTime({})", date_time)),
+    None => line(out, bytes.len() - input.len(), 1, format_args!("DateTime(None)")),
+  }
+
+  data(out, bytes, rest, 1)
+}
+
+/// Dump data items starting at `input`, without recursing so arbitrarily nested input cannot overflow the stack.
+fn data<'i>(out: &mut String, bytes: &[u8], mut input: &'i [u8], indent: usize) -> Option<&'i [u8]> {
+  // The number of items left at each nesting level.
+  let mut pending = vec![1];
+
+  while let Some(left) = pending.last_mut() {
+    if *left == 0 {
+      pending.pop();
+      continue
+    }
+    *left -= 1;
+
+    let offset = bytes.len() - input.len();
+    let indent = indent + pending.len() - 1;
+
+    // Compact arrays cannot be represented by `DataRef`, so dump them as a whole.
+    if input.first() == Some(&DataType::CompactArray.to_tag()) {
+      let (rest, data) = step(out, bytes, input, indent, Data::parse)?;
+      line(out, offset, indent, format_args!("{:02x} {:?}", input[0], data));
+      input = rest;
+      continue
+    }
+
+    let (rest, data) = step(out, bytes, input, indent, DataRef::parse)?;
+    line(out, offset, indent, format_args!("{:02x} {:?}", input[0], data));
+    if let DataRef::Array(len) | DataRef::Structure(len) = data {
+      pending.push(len);
+    }
+    input = rest;
+  }
+
+  Some(input)
+}
+
+/// Run `parser`, annotating its error if it fails.
+fn step<'i, O>(
+  out: &mut String,
+  bytes: &[u8],
+  input: &'i [u8],
+  indent: usize,
+  mut parser: impl FnMut(&'i [u8]) -> IResult<&'i [u8], O>,
+) -> Option<(&'i [u8], O)> {
+  match parser(input) {
+    Ok(result) => Some(result),
+    Err(err) => {
+      error(out, bytes, input, indent, err);
+      None
+    },
+  }
+}
+
+/// Annotate an error which occurred while parsing `input`, at the offset of the item which failed to parse.
+fn error(out: &mut String, bytes: &[u8], input: &[u8], indent: usize, err: nom::Err<nom::error::Error<&[u8]>>) {
+  let offset = bytes.len() - input.len();
+  match err {
+    nom::Err::Incomplete(_) => line(out, offset, indent, format_args!("error: incomplete")),
+    nom::Err::Error(err) | nom::Err::Failure(err) => {
+      line(out, offset, indent, format_args!("error: invalid format ({:?})", err.code))
+    },
+  }
+}
+
+fn line(out: &mut String, offset: usize, indent: usize, args: core::fmt::Arguments<'_>) {
+  // Writing to a `String` cannot fail.
+  let _ = writeln!(out, "{:04x}  {:width$}{}", offset, "", args, width = indent * 2);
+}
@@ -0,0 +1,156 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::{Dlms, DlmsDataLinkLayer, Error, ObisMap};
+
+/// A [`DlmsDataLinkLayer`] whose frames can be split off a raw byte stream.
+///
+/// Frames are reassembled into APDUs using [`DlmsDataLinkLayer::next_frame`].
+pub trait StreamingDataLinkLayer: for<'i, 'f> DlmsDataLinkLayer<'i, &'f [Self::Frame<'i>]> {
+  type Frame<'i>;
+
+  /// Parse a single frame from the start of `input`.
+  fn parse_frame(input: &[u8]) -> Result<(&[u8], Self::Frame<'_>), Error>;
+
+  /// Whether `byte` may be the first byte of a frame, used to resynchronize after invalid input.
+  fn is_frame_start(byte: u8) -> bool;
+
+  /// Whether `frame` carries user data, as opposed to e.g. an acknowledgement.
+  ///
+  /// Frames without user data which are not part of a message are dropped from the buffer.
+  fn has_user_data(_frame: &Self::Frame<'_>) -> bool {
+    true
+  }
+}
+
+/// Incremental decoder for DLMS messages received via a data link layer such as M-Bus or HDLC.
+///
+/// Bytes are buffered internally until a complete (possibly segmented) message
+/// is available, so input can be fed in chunks of any size, e.g. as it is read
+/// from a serial port.
+#[derive(Debug)]
+pub struct DlmsDecoder<Dll> {
+  dlms: Dlms,
+  buffer: Vec<u8>,
+  /// The end offsets of the complete frames at the start of `buffer`.
+  frame_ends: Vec<usize>,
+  /// The length `buffer` needs to reach before parsing the next frame can succeed.
+  needed: usize,
+  data_link_layer: PhantomData<Dll>,
+}
+
+impl<Dll> DlmsDecoder<Dll>
+where
+  Dll: StreamingDataLinkLayer,
+{
+  pub fn new(dlms: Dlms) -> Self {
+    Self { dlms, buffer: Vec::new(), frame_ends: Vec::new(), needed: 0, data_link_layer: PhantomData }
+  }
+
+  /// Feed `bytes` into the decoder and return all messages completed by them.
+  ///
+  /// An empty `Vec` means that more bytes are needed. Bytes which cannot be
+  /// parsed as a frame are skipped up to the next possible start character
+  /// and reported as an error.
+  pub fn push(&mut self, bytes: &[u8]) -> Vec<Result<ObisMap, Error>> {
+    self.buffer.extend_from_slice(bytes);
+
+    let mut results = Vec::new();
+    if self.buffer.len() < self.needed {
+      return results
+    }
+
+    // Only try to decode a message if a frame was completed.
+    if self.parse_frames(&mut results) {
+      let consumed_frames = self.decode(&mut results);
+      if consumed_frames > 0 {
+        let consumed = self.frame_ends[consumed_frames - 1];
+        self.buffer.drain(..consumed);
+        self.frame_ends.drain(..consumed_frames);
+        self.frame_ends.iter_mut().for_each(|end| *end -= consumed);
+        self.needed = self.needed.saturating_sub(consumed);
+      }
+    }
+
+    results
+  }
+
+  /// Whether there are buffered bytes, apart from frame delimiters, which are not part of a complete message yet.
+  pub fn has_pending_input(&self) -> bool {
+    self.buffer.iter().any(|&b| !Dll::is_frame_start(b))
+  }
+
+  /// Find the end offsets of all complete frames after the ones already found, removing
+  /// invalid bytes in between. Returns whether a new frame was found.
+  fn parse_frames(&mut self, results: &mut Vec<Result<ObisMap, Error>>) -> bool {
+    let mut pos = self.frame_ends.last().copied().unwrap_or(0);
+    let found = self.frame_ends.len();
+    self.needed = 0;
+
+    while pos < self.buffer.len() {
+      match Dll::parse_frame(&self.buffer[pos..]).map(|(rest, _)| rest.len()) {
+        Ok(rest_len) => {
+          pos = self.buffer.len() - rest_len;
+          self.frame_ends.push(pos);
+        },
+        Err(Error::Incomplete(needed)) => {
+          self.needed = self.buffer.len() + needed.map_or(1, |n| n.get());
+          break
+        },
+        Err(err) => {
+          results.push(Err(err));
+
+          let skip = self.buffer[(pos + 1)..]
+            .iter()
+            .position(|&b| Dll::is_frame_start(b))
+            .map_or(self.buffer.len() - pos, |i| i + 1);
+          self.buffer.drain(pos..(pos + skip));
+        },
+      }
+    }
+
+    self.frame_ends.len() > found
+  }
+
+  /// Decode all complete messages and return the number of frames consumed.
+  fn decode(&self, results: &mut Vec<Result<ObisMap, Error>>) -> usize {
+    let mut start = 0;
+    let frames = self
+      .frame_ends
+      .iter()
+      .filter_map(|&end| {
+        let frame = Dll::parse_frame(&self.buffer[start..end]).ok().map(|(_, frame)| frame);
+        start = end;
+        frame
+      })
+      .collect::<Vec<_>>();
+
+    let mut input = &frames[..];
+    loop {
+      let skip = input.iter().take_while(|frame| !Dll::has_user_data(frame)).count();
+      input = &input[skip..];
+      if input.is_empty() {
+        break
+      }
+
+      match Dll::next_frame(input) {
+        Ok((rest, frame)) => {
+          results.push(
+            self
+              .dlms
+              .decrypt_frame(&frame, &mut Vec::new())
+              .and_then(|(apdu, meta)| self.dlms.parse_obis_map(&apdu, &meta)),
+          );
+          input = rest;
+        },
+        Err(Error::Incomplete(_)) => break,
+        Err(err) => {
+          results.push(Err(err));
+          input = &input[1..];
+        },
+      }
+    }
+
+    frames.len() - input.len()
+  }
+}
@@ -0,0 +1,92 @@
+use nom::{
+  number::streaming::{be_u16, i8, u8},
+  IResult,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{Data, ObisCode};
+
+/// A reference to an attribute of a COSEM object.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CosemAttributeDescriptor {
+  pub(crate) class_id: u16,
+  pub(crate) instance_id: ObisCode,
+  pub(crate) attribute_id: i8,
+}
+
+impl CosemAttributeDescriptor {
+  pub fn class_id(&self) -> u16 {
+    self.class_id
+  }
+
+  pub fn instance_id(&self) -> &ObisCode {
+    &self.instance_id
+  }
+
+  pub fn attribute_id(&self) -> i8 {
+    self.attribute_id
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, class_id) = be_u16(input)?;
+    let (input, instance_id) = ObisCode::parse(input)?;
+    let (input, attribute_id) = i8(input)?;
+    Ok((input, Self { class_id, instance_id, attribute_id }))
+  }
+}
+
+/// Parameters for selectively accessing an attribute, e.g. a range of entries of a profile.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectiveAccessDescriptor {
+  pub(crate) access_selector: u8,
+  pub(crate) access_parameters: Data,
+}
+
+impl SelectiveAccessDescriptor {
+  pub fn access_selector(&self) -> u8 {
+    self.access_selector
+  }
+
+  pub fn access_parameters(&self) -> &Data {
+    &self.access_parameters
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, access_selector) = u8(input)?;
+    let (input, access_parameters) = Data::parse(input)?;
+    Ok((input, Self { access_selector, access_parameters }))
+  }
+}
+
+/// A reference to a method of a COSEM object.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CosemMethodDescriptor {
+  pub(crate) class_id: u16,
+  pub(crate) instance_id: ObisCode,
+  pub(crate) method_id: i8,
+}
+
+impl CosemMethodDescriptor {
+  pub fn class_id(&self) -> u16 {
+    self.class_id
+  }
+
+  pub fn instance_id(&self) -> &ObisCode {
+    &self.instance_id
+  }
+
+  pub fn method_id(&self) -> i8 {
+    self.method_id
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, class_id) = be_u16(input)?;
+    let (input, instance_id) = ObisCode::parse(input)?;
+    let (input, method_id) = i8(input)?;
+    Ok((input, Self { class_id, instance_id, method_id }))
+  }
+}
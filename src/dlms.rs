@@ -0,0 +1,340 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+
+use aes::Aes128;
+use cipher::Key;
+use nom::combinator::{all_consuming, complete};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::apdu::CipheringOverrides;
+use crate::general_glo_ciphering::{DEFAULT_INVOCATION_COUNTER_WIDTH, VALID_INVOCATION_COUNTER_WIDTHS};
+#[cfg(feature = "std")]
+use crate::ReplayWindow;
+use crate::{map_nom_error, Apdu, DlmsDataLinkLayer, DlmsDataLinkLayerHeader, Error, ObisMap};
+
+pub struct Dlms {
+  key: Key<Aes128>,
+  authentication_key: Option<Key<Aes128>>,
+  invocation_counter_width: usize,
+  #[cfg(feature = "std")]
+  replay_window: Option<std::sync::Mutex<ReplayWindow>>,
+}
+
+impl fmt::Debug for Dlms {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut s = f.debug_struct("Dlms");
+    s.field("key", &format_args!("Key(<redacted>)"));
+    s.field("authentication_key", &self.authentication_key.as_ref().map(|_| format_args!("Key(<redacted>)")));
+    s.field("invocation_counter_width", &self.invocation_counter_width);
+    #[cfg(feature = "std")]
+    s.field("replay_window", &self.replay_window);
+    s.finish()
+  }
+}
+
+/// With the `zeroize` feature, the key is wiped from memory when it is replaced or dropped.
+#[cfg(feature = "zeroize")]
+impl Drop for Dlms {
+  fn drop(&mut self) {
+    self.key.as_mut_slice().zeroize();
+    if let Some(authentication_key) = &mut self.authentication_key {
+      authentication_key.as_mut_slice().zeroize();
+    }
+  }
+}
+
+impl Dlms {
+  pub fn new(key: impl Into<Key<Aes128>>) -> Self {
+    Dlms {
+      key: key.into(),
+      authentication_key: None,
+      invocation_counter_width: DEFAULT_INVOCATION_COUNTER_WIDTH,
+      #[cfg(feature = "std")]
+      replay_window: None,
+    }
+  }
+
+  /// Create a decoder from the 16 bytes of an AES-128 key.
+  ///
+  /// This is equivalent to [`Dlms::new`], but makes the expected key length explicit.
+  /// A key stored in a slice can be converted using `<[u8; 16]>::try_from`, which
+  /// fails if it has the wrong length.
+  ///
+  /// ```
+  /// use std::convert::TryFrom;
+  ///
+  /// use dlms_cosem::Dlms;
+  ///
+  /// let key: &[u8] = &[0x42; 16];
+  /// let dlms = Dlms::from_key_bytes(<[u8; 16]>::try_from(key).unwrap());
+  /// ```
+  pub fn from_key_bytes(bytes: [u8; 16]) -> Self {
+    Self::new(bytes)
+  }
+
+  /// Create a decoder from an AES-128 key given as 32 hexadecimal digits.
+  ///
+  /// Returns [`Error::InvalidFormat`] if `key` has the wrong length or contains invalid digits.
+  ///
+  /// ```
+  /// use dlms_cosem::Dlms;
+  ///
+  /// assert!(Dlms::from_hex_key("000102030405060708090A0B0C0D0E0F").is_ok());
+  /// assert!(Dlms::from_hex_key("0001020304").is_err());
+  /// assert!(Dlms::from_hex_key("+00102030405060708090A0B0C0D0E0F").is_err());
+  /// ```
+  pub fn from_hex_key(key: &str) -> Result<Self, Error> {
+    if key.len() != 32 {
+      return Err(Error::InvalidFormat)
+    }
+
+    let mut bytes = [0u8; 16];
+    let digit = |c: u8| (c as char).to_digit(16).ok_or(Error::InvalidFormat);
+    for (byte, digits) in bytes.iter_mut().zip(key.as_bytes().chunks(2)) {
+      *byte = (digit(digits[0])? << 4 | digit(digits[1])?) as u8;
+    }
+
+    Ok(Self::from_key_bytes(bytes))
+  }
+
+  /// Reject frames whose invocation counter is not greater than the last one seen from the same system title.
+  ///
+  /// The state is kept behind a lock, so decrypting still only needs `&self` and a `Dlms` can be shared
+  /// between threads. Without `std`, use a [`ReplayWindow`] directly.
+  #[cfg(feature = "std")]
+  pub fn with_replay_protection(mut self) -> Self {
+    self.replay_window = Some(Default::default());
+    self
+  }
+
+  /// Verify the authentication tag of authenticated frames using `authentication_key`.
+  ///
+  /// Authenticated frames are rejected with [`Error::DecryptionFailed`] without an authentication key.
+  pub fn with_authentication_key(mut self, authentication_key: impl Into<Key<Aes128>>) -> Self {
+    self.authentication_key = Some(authentication_key.into());
+    self
+  }
+
+  /// Parse invocation counters as `width` bytes instead of the standard 4 bytes.
+  ///
+  /// Some legacy meters use a 1 or 2 byte frame counter in the security header.
+  ///
+  /// Returns [`Error::InvalidFormat`] if `width` is not between 1 and 4, since the counter
+  /// has to fit into the initialization vector.
+  pub fn with_invocation_counter_width(mut self, width: usize) -> Result<Self, Error> {
+    if !VALID_INVOCATION_COUNTER_WIDTHS.contains(&width) {
+      return Err(Error::InvalidFormat)
+    }
+
+    self.invocation_counter_width = width;
+    Ok(self)
+  }
+
+  /// Replace the key used for decrypting subsequent frames, e.g. after a key change.
+  pub fn set_key(&mut self, key: impl Into<Key<Aes128>>) {
+    #[cfg(feature = "zeroize")]
+    self.key.as_mut_slice().zeroize();
+    self.key = key.into();
+  }
+
+  pub fn decrypt<'i, Dll, I>(&self, input: I) -> Result<(I, ObisMap), Error>
+  where
+    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
+  {
+    let (output, obis, _) = self.decrypt_with_meta::<Dll, _>(input)?;
+
+    Ok((output, obis))
+  }
+
+  /// Like [`decrypt`](Self::decrypt), but return the number of frames consumed from `input`
+  /// instead of the remaining frames, e.g. for advancing a ring buffer.
+  pub fn decrypt_one<'i, 'f, Dll, F>(&self, input: &'f [F]) -> Result<(usize, ObisMap), Error>
+  where
+    Dll: DlmsDataLinkLayer<'i, &'f [F]> + ?Sized,
+  {
+    let (rest, obis) = self.decrypt::<Dll, _>(input)?;
+
+    Ok((input.len() - rest.len(), obis))
+  }
+
+  /// Like [`decrypt`](Self::decrypt), but also return [`Metadata`] describing how the frame was decoded.
+  pub fn decrypt_with_meta<'i, Dll, I>(&self, input: I) -> Result<(I, ObisMap, Metadata), Error>
+  where
+    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
+  {
+    let (output, frame, segment_count) = Dll::next_frame_with_segment_count(input)?;
+    let (apdu, mut meta) = self.decrypt_frame(frame.borrow(), &mut Vec::new())?;
+    meta.segment_count = segment_count;
+
+    let obis = self.parse_obis_map(&apdu, &meta)?;
+
+    Ok((output, obis, meta))
+  }
+
+  pub fn decrypt_apdu<'i, Dll, I>(&self, input: I) -> Result<(I, Apdu), Error>
+  where
+    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
+  {
+    let (output, frame) = Dll::next_frame(input)?;
+    let (apdu, meta) = self.decrypt_frame(frame.borrow(), &mut Vec::new())?;
+    self.check_replay(&meta)?;
+
+    Ok((output, apdu))
+  }
+
+  /// Like [`decrypt`](Self::decrypt), but use the system title from the link layer header
+  /// for decryption instead of the one in the APDU, if the header contains one.
+  ///
+  /// This is needed for meters, e.g. wireless M-Bus meters, which only transmit their system
+  /// title in the link layer header and put something else in its place in the APDU.
+  pub fn decrypt_with_header_system_title<'i, Dll, I>(&self, input: I) -> Result<(I, ObisMap), Error>
+  where
+    Dll: DlmsDataLinkLayerHeader<'i, I> + ?Sized,
+  {
+    let (output, frame, header) = Dll::next_frame_with_header(input)?;
+    let (apdu, meta) =
+      self.decrypt_frame_with_overrides(frame.borrow(), Dll::system_title(&header), None, &mut Vec::new())?;
+
+    let obis = self.parse_obis_map(&apdu, &meta)?;
+
+    Ok((output, obis))
+  }
+
+  /// Like [`decrypt`](Self::decrypt), but use `invocation_counter` for the initialization vector
+  /// instead of the invocation counter in the APDU.
+  ///
+  /// This is only needed for meters which derive the counter part of the initialization vector
+  /// from a different source, e.g. a counter which is reset on reboot.
+  pub fn decrypt_with_invocation_counter<'i, Dll, I>(
+    &self,
+    input: I,
+    invocation_counter: u32,
+  ) -> Result<(I, ObisMap), Error>
+  where
+    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
+  {
+    let (output, frame) = Dll::next_frame(input)?;
+    let (apdu, meta) =
+      self.decrypt_frame_with_overrides(frame.borrow(), None, Some(invocation_counter), &mut Vec::new())?;
+
+    let obis = self.parse_obis_map(&apdu, &meta)?;
+
+    Ok((output, obis))
+  }
+
+  /// Like [`decrypt`](Self::decrypt), but reuse the buffers in `scratch` across calls instead of
+  /// allocating new ones for reassembling and decrypting each frame.
+  pub fn decrypt_into<'i, Dll, I>(&self, input: I, scratch: &mut DecodeScratch) -> Result<(I, ObisMap), Error>
+  where
+    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
+  {
+    let output = Dll::next_frame_into(input, &mut scratch.frame)?;
+    let (apdu, meta) = self.decrypt_frame(&scratch.frame, &mut scratch.payload)?;
+
+    let obis = self.parse_obis_map(&apdu, &meta)?;
+
+    Ok((output, obis))
+  }
+
+  pub(crate) fn decrypt_frame(&self, frame: &[u8], buffer: &mut Vec<u8>) -> Result<(Apdu, Metadata), Error> {
+    self.decrypt_frame_with_overrides(frame, None, None, buffer)
+  }
+
+  fn decrypt_frame_with_overrides(
+    &self,
+    frame: &[u8],
+    system_title: Option<[u8; 8]>,
+    invocation_counter: Option<u32>,
+    buffer: &mut Vec<u8>,
+  ) -> Result<(Apdu, Metadata), Error> {
+    let overrides = CipheringOverrides { system_title, invocation_counter };
+    let (_, (apdu, meta)) = map_nom_error(all_consuming(complete(|input| {
+      Apdu::parse_with_meta(
+        input,
+        &self.key,
+        self.authentication_key.as_ref(),
+        overrides,
+        self.invocation_counter_width,
+        buffer,
+      )
+    }))(frame))?;
+
+    Ok((apdu, meta))
+  }
+
+  /// Parse the registers of a decrypted APDU, only updating the replay window if this succeeds.
+  pub(crate) fn parse_obis_map(&self, apdu: &Apdu, meta: &Metadata) -> Result<ObisMap, Error> {
+    let (_, obis) = ObisMap::parse(apdu).map_err(|_| Error::InvalidFormat)?;
+    self.check_replay(meta)?;
+
+    Ok(obis)
+  }
+
+  /// Check the invocation counter of a completely decoded frame against the replay window.
+  ///
+  /// This must only be called once the frame was authenticated, if applicable, and parsed,
+  /// so a forged or malformed frame cannot advance the window.
+  fn check_replay(&self, _meta: &Metadata) -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    if let Some(replay_window) = &self.replay_window {
+      replay_window.lock().unwrap_or_else(|err| err.into_inner()).check(_meta)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Reusable buffers for [`Dlms::decrypt_into`].
+#[derive(Debug, Default)]
+pub struct DecodeScratch {
+  frame: Vec<u8>,
+  payload: Vec<u8>,
+}
+
+impl DecodeScratch {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+/// Information about how a frame was decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Metadata {
+  pub(crate) ciphered: bool,
+  pub(crate) system_title: Option<[u8; 8]>,
+  pub(crate) invocation_counter: Option<u32>,
+  pub(crate) segment_count: usize,
+}
+
+impl Metadata {
+  /// Whether the APDU was wrapped in a ciphering envelope.
+  ///
+  /// If this is `false`, the frame was sent in the clear and its contents were parsed
+  /// directly from the input without being decrypted.
+  pub fn ciphered(&self) -> bool {
+    self.ciphered
+  }
+
+  /// The system title of the sender of a ciphered APDU.
+  pub fn system_title(&self) -> Option<&[u8; 8]> {
+    self.system_title.as_ref()
+  }
+
+  /// The invocation counter of a ciphered APDU.
+  pub fn invocation_counter(&self) -> Option<u32> {
+    self.invocation_counter
+  }
+
+  /// The number of data link layer segments the frame was reassembled from.
+  pub fn segment_count(&self) -> usize {
+    self.segment_count
+  }
+
+  /// Whether the frame was split into multiple segments by the data link layer.
+  pub fn segmented(&self) -> bool {
+    self.segment_count > 1
+  }
+}
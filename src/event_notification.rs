@@ -0,0 +1,37 @@
+use nom::{combinator::map, IResult};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{data::parse_optional, CosemAttributeDescriptor, Data, DateTime};
+
+/// An `EventNotificationRequest` APDU, which is pushed by a meter to report
+/// an event such as a power failure.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventNotification {
+  pub(crate) time: Option<DateTime>,
+  pub(crate) attribute_descriptor: CosemAttributeDescriptor,
+  pub(crate) attribute_value: Data,
+}
+
+impl EventNotification {
+  /// The time at which the event occurred, if the meter included one.
+  pub fn time(&self) -> Option<&DateTime> {
+    self.time.as_ref()
+  }
+
+  pub fn attribute_descriptor(&self) -> &CosemAttributeDescriptor {
+    &self.attribute_descriptor
+  }
+
+  pub fn attribute_value(&self) -> &Data {
+    &self.attribute_value
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, time) = map(parse_optional(DateTime::parse_octet_string), Option::flatten)(input)?;
+    let (input, attribute_descriptor) = CosemAttributeDescriptor::parse(input)?;
+    let (input, attribute_value) = Data::parse(input)?;
+    Ok((input, Self { time, attribute_descriptor, attribute_value }))
+  }
+}
@@ -0,0 +1,244 @@
+//! Builders for encoded test fixtures, so parser tests can be written in terms of their contents.
+
+use alloc::{vec, vec::Vec};
+
+use aes::Aes128;
+use aes_gcm::aead::{consts::U12, AeadInPlace, KeyInit};
+use aes_gcm::AesGcm;
+
+use crate::{Data, DataType, Date, DateTime, Time};
+
+/// Encode an A-XDR length.
+pub(crate) fn length(len: usize, bytes: &mut Vec<u8>) {
+  if len < 0x80 {
+    bytes.push(len as u8);
+  } else {
+    let len = (len as u32).to_be_bytes();
+    let skip = len.iter().take_while(|&&b| b == 0).count();
+    bytes.push(0x80 | (len.len() - skip) as u8);
+    bytes.extend(&len[skip..]);
+  }
+}
+
+/// Encode a data item including its type tag.
+pub(crate) fn data(data: &Data, bytes: &mut Vec<u8>) {
+  macro_rules! tagged {
+    ($data_type:ident, $value:expr) => {{
+      bytes.push(DataType::$data_type.to_tag());
+      bytes.extend($value.to_be_bytes());
+    }};
+  }
+
+  match data {
+    Data::Null => bytes.push(DataType::Null.to_tag()),
+    Data::OctetString(s) => {
+      bytes.push(DataType::OctetString.to_tag());
+      length(s.len(), bytes);
+      bytes.extend(s);
+    },
+    Data::VisibleString(s) => {
+      bytes.push(DataType::VisibleString.to_tag());
+      length(s.len(), bytes);
+      bytes.extend(s.as_bytes());
+    },
+    Data::Utf8String(s) => {
+      bytes.push(DataType::Utf8String.to_tag());
+      length(s.len(), bytes);
+      bytes.extend(s.as_bytes());
+    },
+    Data::Integer(n) => tagged!(Integer, n),
+    Data::Unsigned(n) => tagged!(Unsigned, n),
+    Data::Long(n) => tagged!(Long, n),
+    Data::LongUnsigned(n) => tagged!(LongUnsigned, n),
+    Data::DoubleLong(n) => tagged!(DoubleLong, n),
+    Data::DoubleLongUnsigned(n) => tagged!(DoubleLongUnsigned, n),
+    Data::Long64(n) => tagged!(Long64, n),
+    Data::Long64Unsigned(n) => tagged!(Long64Unsigned, n),
+    Data::Float32(n) => tagged!(Float32, n),
+    Data::Float64(n) => tagged!(Float64, n),
+    Data::Enum(n) => tagged!(Enum, n),
+    Data::Bool(b) => tagged!(Bool, (*b as u8)),
+    Data::Bcd(n) => tagged!(BinaryCodedDecimal, n),
+    Data::DateTime(date_time) => {
+      bytes.push(DataType::DateTime.to_tag());
+      self::date_time(date_time, bytes);
+    },
+    Data::Date(date) => {
+      bytes.push(DataType::Date.to_tag());
+      self::date(date, bytes);
+    },
+    Data::Time(time) => {
+      bytes.push(DataType::Time.to_tag());
+      self::time(time, bytes);
+    },
+    Data::DeltaInteger(n) => tagged!(DeltaInteger, n),
+    Data::DeltaLong(n) => tagged!(DeltaLong, n),
+    Data::DeltaDoubleLong(n) => tagged!(DeltaDoubleLong, n),
+    Data::DeltaUnsigned(n) => tagged!(DeltaUnsigned, n),
+    Data::DeltaLongUnsigned(n) => tagged!(DeltaLongUnsigned, n),
+    Data::DeltaDoubleLongUnsigned(n) => tagged!(DeltaDoubleLongUnsigned, n),
+    Data::BitString(bits) => {
+      bytes.push(DataType::BitString.to_tag());
+      length(bits.len(), bytes);
+      bytes.extend(bits.bytes());
+    },
+    Data::Array(items) | Data::Structure(items) => {
+      let data_type = if let Data::Array(_) = data { DataType::Array } else { DataType::Structure };
+      bytes.push(data_type.to_tag());
+      length(items.len(), bytes);
+      items.iter().for_each(|item| self::data(item, bytes));
+    },
+  }
+}
+
+/// Encode a date without its type tag.
+pub(crate) fn date(date: &Date, bytes: &mut Vec<u8>) {
+  bytes.extend(date.year.to_be_bytes());
+  bytes.extend([date.month, date.day_of_month, date.day_of_week]);
+}
+
+/// Encode a time without its type tag, with unspecified fields as `0xff`.
+pub(crate) fn time(time: &Time, bytes: &mut Vec<u8>) {
+  bytes.extend([time.hour, time.minute, time.second, time.hundredth].map(|field| field.unwrap_or(0xff)));
+}
+
+/// Encode a date-time without its type tag, as parsed by [`DateTime::parse`].
+pub(crate) fn date_time(date_time: &DateTime, bytes: &mut Vec<u8>) {
+  date(&date_time.date, bytes);
+  time(&date_time.time, bytes);
+  bytes.extend(date_time.offset_minutes.unwrap_or(0x8000u16 as i16).to_be_bytes());
+  bytes.push(date_time.clock_status.map_or(0xff, |clock_status| clock_status.0));
+}
+
+/// Build a register structure from its OBIS code, value and optional scaler and unit code.
+pub(crate) fn register(obis_code: [u8; 6], value: Data, scaler_unit: Option<(i8, u8)>) -> Data {
+  let mut items = vec![Data::OctetString(obis_code.to_vec()), value];
+  if let Some((scaler, unit)) = scaler_unit {
+    items.push(Data::Structure(vec![Data::Integer(scaler), Data::Enum(unit)]));
+  }
+  Data::Structure(items)
+}
+
+/// Encode a data-notification APDU without a date and time, with `registers` as its body.
+pub(crate) fn data_notification(registers: Vec<Data>) -> Vec<u8> {
+  let mut bytes = vec![
+    0x0f, // Tag
+    0x00, 0x00, 0x00, 0x01, // Long Invoke ID & Priority
+    0x00, // Date & Time (empty Octet String)
+  ];
+  data(&Data::Structure(registers), &mut bytes);
+  bytes
+}
+
+/// Encode a data-notification APDU containing a single unscaled active energy register (1-0:1.8.0*255).
+pub(crate) fn energy_notification(value: u32) -> Vec<u8> {
+  data_notification(vec![register([1, 0, 1, 8, 0, 255], Data::DoubleLongUnsigned(value), None)])
+}
+
+/// Encrypt `payload` and wrap it in a general-glo-ciphering APDU using security suite 0 without authentication.
+pub(crate) fn glo_ciphering(
+  key: &[u8; 16],
+  system_title: [u8; 8],
+  counter: u32,
+  payload: &[u8],
+) -> Result<Vec<u8>, aes_gcm::Error> {
+  glo_ciphering_with_authentication(key, None, system_title, counter, payload)
+}
+
+/// Like [`glo_ciphering`], but authenticate the APDU using `authentication_key` if given,
+/// appending the authentication tag to the payload.
+pub(crate) fn glo_ciphering_with_authentication(
+  key: &[u8; 16],
+  authentication_key: Option<&[u8; 16]>,
+  system_title: [u8; 8],
+  counter: u32,
+  payload: &[u8],
+) -> Result<Vec<u8>, aes_gcm::Error> {
+  let mut iv = [0; 12];
+  iv[..8].copy_from_slice(&system_title);
+  iv[8..].copy_from_slice(&counter.to_be_bytes());
+
+  // Security Control (encryption, suite 0)
+  let security_control = if authentication_key.is_some() { 0x30 } else { 0x20 };
+
+  let mut associated_data = vec![security_control];
+  associated_data.extend(authentication_key.into_iter().flatten());
+
+  let mut payload = payload.to_vec();
+  let tag = AesGcm::<Aes128, U12, U12>::new(key.into()).encrypt_in_place_detached(
+    (&iv).into(),
+    &associated_data,
+    &mut payload,
+  )?;
+  if authentication_key.is_some() {
+    payload.extend(tag);
+  }
+
+  let mut bytes = vec![0xdb, 0x08];
+  bytes.extend(system_title);
+
+  length(payload.len() + 5, &mut bytes);
+
+  bytes.push(security_control);
+  bytes.extend(counter.to_be_bytes());
+  bytes.extend(payload);
+  Ok(bytes)
+}
+
+/// Wrap `information` in an unsegmented HDLC frame.
+#[cfg(feature = "hdlcparse")]
+pub(crate) fn hdlc_frame(information: &[u8]) -> Vec<u8> {
+  hdlc_segment(information, false)
+}
+
+/// Wrap `information` in an HDLC frame, setting the segmentation bit if `segmented`.
+#[cfg(feature = "hdlcparse")]
+pub(crate) fn hdlc_segment(information: &[u8], segmented: bool) -> Vec<u8> {
+  fn fcs(data: &[u8]) -> [u8; 2] {
+    let fcs = data.iter().fold(0xffffu16, |fcs, &b| {
+      (0..8).fold(fcs ^ b as u16, |fcs, _| if fcs & 1 != 0 { (fcs >> 1) ^ 0x8408 } else { fcs >> 1 })
+    });
+    (!fcs).to_le_bytes()
+  }
+
+  let format = if segmented { 0xa8 } else { 0xa0 };
+  let mut frame = vec![format, information.len() as u8 + 9, 0x03, 0x21, 0x13];
+  frame.extend(fcs(&frame));
+  frame.extend(information);
+  frame.extend(fcs(&frame));
+  frame.insert(0, 0x7e);
+  frame.push(0x7e);
+  frame
+}
+
+/// Wrap `user_data` in an M-Bus long frame with the given CI-field.
+#[cfg(feature = "mbusparse")]
+pub(crate) fn mbus_long_frame(control_information: u8, user_data: &[u8]) -> Vec<u8> {
+  let len = user_data.len() as u8 + 3;
+  let mut frame = vec![0x68, len, len, 0x68, 0x53, 0xff, control_information];
+  frame.extend(user_data);
+  frame.push(frame[4..].iter().fold(0u8, |sum, &b| sum.wrapping_add(b)));
+  frame.push(0x16);
+  frame
+}
+
+/// Split `apdu` into M-Bus long frames with segments of up to 200 bytes.
+#[cfg(feature = "mbusparse")]
+pub(crate) fn segmented_mbus_message(apdu: &[u8]) -> Vec<u8> {
+  segmented_mbus_message_with_len(apdu, 200)
+}
+
+/// Like [`segmented_mbus_message`], but with segments of up to `segment_len` bytes.
+#[cfg(feature = "mbusparse")]
+pub(crate) fn segmented_mbus_message_with_len(apdu: &[u8], segment_len: usize) -> Vec<u8> {
+  let segments = apdu.len().div_ceil(segment_len);
+
+  let mut bytes = Vec::new();
+  for (segment, chunk) in apdu.chunks(segment_len).enumerate() {
+    let last_segment = if segment == segments - 1 { 0x10 } else { 0x00 };
+    let mut user_data = vec![0x01, 0x67];
+    user_data.extend(chunk);
+    bytes.extend(mbus_long_frame(segment as u8 | last_segment, &user_data));
+  }
+  bytes
+}
@@ -1,37 +1,92 @@
 use alloc::vec::Vec;
 
 use aes::Aes128;
-use aes_gcm::aead::{AeadInPlace, NewAead};
-use aes_gcm::Aes128Gcm;
+use aes_gcm::aead::{consts::U12, AeadInPlace, KeyInit};
+use aes_gcm::{AesGcm, Tag};
 use cipher::Key;
-use nom::{
-  bytes::streaming::tag,
-  combinator::cond,
-  multi::{count, fill},
-  number::streaming::{be_u16, be_u32, u8},
-  IResult,
-};
+use nom::{bytes::streaming::take, combinator::fail, multi::fill, number::streaming::u8, IResult};
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
-use crate::SecurityControl;
+use crate::{data::parse_length, SecurityControl};
 
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GeneralGloCiphering {
-  system_title: [u8; 8],
+  system_title: Option<[u8; 8]>,
   security_control: SecurityControl,
   invocation_counter: Option<u32>,
   payload: Vec<u8>,
 }
 
 impl GeneralGloCiphering {
-  pub fn decrypt(mut self, key: &Key<Aes128>) -> Result<Vec<u8>, aes_gcm::Error> {
-    if self.security_control.encryption() {
-      let cipher = Aes128Gcm::new(key);
+  /// The system title of the sender, or `None` if it is implied by the transport.
+  pub fn system_title(&self) -> Option<&[u8; 8]> {
+    self.system_title.as_ref()
+  }
+
+  pub fn security_control(&self) -> &SecurityControl {
+    &self.security_control
+  }
+
+  /// Whether the payload is encrypted, according to the security control.
+  pub fn is_encrypted(&self) -> bool {
+    self.security_control.encryption()
+  }
+
+  pub fn invocation_counter(&self) -> Option<u32> {
+    self.invocation_counter
+  }
 
-      let mut iv = [0u8; 12];
-      iv[0..8].copy_from_slice(&self.system_title);
-      iv[8..].copy_from_slice(&self.invocation_counter.unwrap().to_be_bytes());
+  /// Override the system title used for the initialization vector.
+  ///
+  /// This is needed for meters which only transmit their system title in
+  /// the link layer header, e.g. wireless M-Bus meters.
+  pub fn set_system_title(&mut self, system_title: [u8; 8]) {
+    self.system_title = Some(system_title);
+  }
+
+  /// Override the invocation counter used for the initialization vector.
+  ///
+  /// This is only needed for meters which derive the counter part of the
+  /// initialization vector from a different source than the APDU.
+  pub fn set_invocation_counter(&mut self, invocation_counter: Option<u32>) {
+    self.invocation_counter = invocation_counter;
+  }
+
+  /// The initialization vector used for decryption, i.e. the system title followed by the
+  /// big-endian invocation counter, or `None` if there is no system title or invocation counter.
+  pub fn initialization_vector(&self) -> Option<[u8; 12]> {
+    initialization_vector(self.system_title.as_ref(), self.invocation_counter)
+  }
+
+  /// Decrypt the payload.
+  ///
+  /// Authenticated payloads cannot be verified without an authentication key, so decrypting them fails.
+  /// Use [`decrypt_with_authentication_key`](Self::decrypt_with_authentication_key) instead.
+  pub fn decrypt(self, key: &Key<Aes128>) -> Result<Vec<u8>, aes_gcm::Error> {
+    self.decrypt_inner(key, None)
+  }
 
-      cipher.encrypt_in_place_detached(&iv.into(), &[], &mut self.payload)?;
+  /// Like [`decrypt`](Self::decrypt), but verify the authentication tag of authenticated payloads
+  /// using `authentication_key`, returning the payload without its tag.
+  pub fn decrypt_with_authentication_key(
+    self,
+    key: &Key<Aes128>,
+    authentication_key: &Key<Aes128>,
+  ) -> Result<Vec<u8>, aes_gcm::Error> {
+    self.decrypt_inner(key, Some(authentication_key))
+  }
+
+  fn decrypt_inner(
+    mut self,
+    key: &Key<Aes128>,
+    authentication_key: Option<&Key<Aes128>>,
+  ) -> Result<Vec<u8>, aes_gcm::Error> {
+    if self.security_control.encryption() || self.security_control.authentication() {
+      let iv = self.initialization_vector().ok_or(aes_gcm::Error)?;
+
+      decrypt_in_place(key, authentication_key, &self.security_control, &iv, &mut self.payload)?;
       self.security_control.set_encryption(false);
     }
 
@@ -39,27 +94,170 @@ impl GeneralGloCiphering {
   }
 
   pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
-    let (input, _) = tag([8])(input)?;
-    let mut system_title = [0u8; 8];
-    let (input, _) = fill(u8, &mut system_title)(input)?;
-
-    let (input, mut payload_len) = match u8(input)? {
-      (input, 0x82) => {
-        let (input, len) = be_u16(input)?;
-        (input, len as usize)
+    Self::parse_with_counter_width(input, DEFAULT_INVOCATION_COUNTER_WIDTH)
+  }
+
+  /// Like [`parse`](Self::parse), but for meters which use an invocation counter of
+  /// `counter_width` bytes instead of the standard 4 bytes.
+  ///
+  /// Fails if `counter_width` is not between 1 and 4.
+  ///
+  /// ```
+  /// use dlms_cosem::GeneralGloCiphering;
+  ///
+  /// #[rustfmt::skip]
+  /// let bytes = [
+  ///   0x08, 0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9, // System Title
+  ///   0x04, // Length
+  ///   0x20, // Security Control
+  ///   0x01, 0x02, // Invocation Counter
+  ///   0xff, // Payload
+  /// ];
+  ///
+  /// let (_, ciphering) = GeneralGloCiphering::parse_with_counter_width(&bytes, 2).unwrap();
+  /// assert_eq!(ciphering.invocation_counter(), Some(0x0102));
+  /// assert!(GeneralGloCiphering::parse(&bytes).is_err());
+  /// ```
+  pub fn parse_with_counter_width(input: &[u8], counter_width: usize) -> IResult<&[u8], Self> {
+    let (input, ciphering) = GeneralGloCipheringRef::parse(input, counter_width)?;
+    let GeneralGloCipheringRef { system_title, security_control, invocation_counter, payload } = ciphering;
+
+    Ok((input, Self { system_title, security_control, invocation_counter, payload: payload.to_vec() }))
+  }
+}
+
+/// A general-glo-ciphering APDU borrowing its payload from the input.
+pub(crate) struct GeneralGloCipheringRef<'i> {
+  pub(crate) system_title: Option<[u8; 8]>,
+  pub(crate) security_control: SecurityControl,
+  pub(crate) invocation_counter: Option<u32>,
+  pub(crate) payload: &'i [u8],
+}
+
+impl<'i> GeneralGloCipheringRef<'i> {
+  pub(crate) fn parse(input: &'i [u8], counter_width: usize) -> IResult<&'i [u8], Self> {
+    if !VALID_INVOCATION_COUNTER_WIDTHS.contains(&counter_width) {
+      return fail(input)
+    }
+
+    // The system title may be omitted if it is implied by the transport.
+    let (input, system_title) = match u8(input)? {
+      (input, 0) => (input, None),
+      (input, 8) => {
+        let mut system_title = [0u8; 8];
+        let (input, _) = fill(u8, &mut system_title)(input)?;
+        (input, Some(system_title))
       },
-      (input, len) => (input, len as usize),
+      (input, _) => return fail(input),
+    };
+
+    let (input, len) = parse_length(input)?;
+    let payload_len = match len.checked_sub(1 + counter_width) {
+      Some(payload_len) => payload_len,
+      None => return fail(input),
     };
-    payload_len -= 5;
 
     // Green Book 9.2.7.2.4.1
     let (input, security_control) = SecurityControl::parse(input)?;
 
-    let (input, invocation_counter) =
-      cond(security_control.authentication() || security_control.encryption(), be_u32)(input)?;
+    let (input, invocation_counter) = if security_control.authentication() || security_control.encryption() {
+      let (input, bytes) = take(counter_width)(input)?;
+      (input, Some(bytes.iter().fold(0, |counter, &b| counter << 8 | u32::from(b))))
+    } else {
+      (input, None)
+    };
 
-    let (input, payload) = count(u8, payload_len)(input)?;
+    let (input, payload) = take(payload_len)(input)?;
 
     Ok((input, Self { system_title, security_control, invocation_counter, payload }))
   }
+
+  /// The ciphering envelope, i.e. everything except the payload.
+  pub(crate) fn envelope(&self) -> GeneralGloCiphering {
+    GeneralGloCiphering {
+      system_title: self.system_title,
+      security_control: self.security_control.clone(),
+      invocation_counter: self.invocation_counter,
+      payload: Vec::new(),
+    }
+  }
+
+  /// Decrypt the payload into `buffer`, reusing its allocation.
+  pub(crate) fn decrypt_into(
+    &self,
+    key: &Key<Aes128>,
+    authentication_key: Option<&Key<Aes128>>,
+    buffer: &mut Vec<u8>,
+  ) -> Result<(), aes_gcm::Error> {
+    buffer.clear();
+    buffer.extend_from_slice(self.payload);
+
+    if self.security_control.encryption() || self.security_control.authentication() {
+      let iv = initialization_vector(self.system_title.as_ref(), self.invocation_counter).ok_or(aes_gcm::Error)?;
+      decrypt_in_place(key, authentication_key, &self.security_control, &iv, buffer)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// The width of the invocation counter in bytes, as specified by the Green Book.
+pub(crate) const DEFAULT_INVOCATION_COUNTER_WIDTH: usize = 4;
+
+/// The supported widths of the invocation counter in bytes, limited by the 4 bytes it occupies
+/// in the initialization vector.
+pub(crate) const VALID_INVOCATION_COUNTER_WIDTHS: core::ops::RangeInclusive<usize> = 1..=4;
+
+fn initialization_vector(system_title: Option<&[u8; 8]>, invocation_counter: Option<u32>) -> Option<[u8; 12]> {
+  let system_title = system_title?;
+  let invocation_counter = invocation_counter?;
+
+  let mut iv = [0u8; 12];
+  iv[0..8].copy_from_slice(system_title);
+  iv[8..].copy_from_slice(&invocation_counter.to_be_bytes());
+  Some(iv)
+}
+
+/// AES-GCM with the 12 byte authentication tag used by security suites 0 and 1.
+type Aes128Gcm = AesGcm<Aes128, U12, U12>;
+
+/// The length of the authentication tag at the end of authenticated payloads.
+const TAG_LEN: usize = 12;
+
+/// Decrypt `payload` and verify its authentication tag, if it has one, removing it from `payload`.
+///
+/// The tag is checked before any decrypted data is returned, so an authenticated
+/// payload which was tampered with is rejected as a whole.
+fn decrypt_in_place(
+  key: &Key<Aes128>,
+  authentication_key: Option<&Key<Aes128>>,
+  security_control: &SecurityControl,
+  iv: &[u8; 12],
+  payload: &mut Vec<u8>,
+) -> Result<(), aes_gcm::Error> {
+  let cipher = Aes128Gcm::new(key);
+
+  if !security_control.authentication() {
+    // Without authentication, GCM is plain CTR mode, so encryption and decryption are the same operation.
+    cipher.encrypt_in_place_detached(iv.into(), &[], payload)?;
+    return Ok(())
+  }
+
+  // Green Book 9.2.7.2.4.2: The associated data consists of the security control byte and the
+  // authentication key, followed by the plaintext if it is not encrypted.
+  let authentication_key = authentication_key.ok_or(aes_gcm::Error)?;
+  let tag_start = payload.len().checked_sub(TAG_LEN).ok_or(aes_gcm::Error)?;
+  let tag = Tag::<U12>::clone_from_slice(&payload[tag_start..]);
+  payload.truncate(tag_start);
+
+  let mut associated_data = Vec::with_capacity(1 + authentication_key.len());
+  associated_data.push(security_control.bits());
+  associated_data.extend_from_slice(authentication_key);
+
+  if security_control.encryption() {
+    cipher.decrypt_in_place_detached(iv.into(), &associated_data, payload, &tag)
+  } else {
+    associated_data.extend_from_slice(payload);
+    cipher.decrypt_in_place_detached(iv.into(), &associated_data, &mut [], &tag)
+  }
 }
@@ -0,0 +1,69 @@
+use nom::{bytes::streaming::tag, IResult};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+  data::parse_optional, data_access_result::parse_get_data_result, CosemAttributeDescriptor, Data, DataAccessResult,
+  InvokeIdAndPriority, SelectiveAccessDescriptor,
+};
+
+/// A `GET-Request` APDU.
+///
+/// Only the `get-request-normal` form is supported.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetRequest {
+  pub(crate) invoke_id_and_priority: InvokeIdAndPriority,
+  pub(crate) attribute_descriptor: CosemAttributeDescriptor,
+  pub(crate) access_selection: Option<SelectiveAccessDescriptor>,
+}
+
+impl GetRequest {
+  pub fn invoke_id_and_priority(&self) -> &InvokeIdAndPriority {
+    &self.invoke_id_and_priority
+  }
+
+  pub fn attribute_descriptor(&self) -> &CosemAttributeDescriptor {
+    &self.attribute_descriptor
+  }
+
+  /// The selective access parameters, e.g. the range of entries requested from a profile.
+  pub fn access_selection(&self) -> Option<&SelectiveAccessDescriptor> {
+    self.access_selection.as_ref()
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, _) = tag([1])(input)?;
+    let (input, invoke_id_and_priority) = InvokeIdAndPriority::parse(input)?;
+    let (input, attribute_descriptor) = CosemAttributeDescriptor::parse(input)?;
+    let (input, access_selection) = parse_optional(SelectiveAccessDescriptor::parse)(input)?;
+    Ok((input, Self { invoke_id_and_priority, attribute_descriptor, access_selection }))
+  }
+}
+
+/// A `GET-Response` APDU.
+///
+/// Only the `get-response-normal` form is supported.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetResponse {
+  pub(crate) invoke_id_and_priority: InvokeIdAndPriority,
+  pub(crate) result: Result<Data, DataAccessResult>,
+}
+
+impl GetResponse {
+  pub fn invoke_id_and_priority(&self) -> &InvokeIdAndPriority {
+    &self.invoke_id_and_priority
+  }
+
+  pub fn result(&self) -> Result<&Data, DataAccessResult> {
+    self.result.as_ref().map_err(|&err| err)
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, _) = tag([1])(input)?;
+    let (input, invoke_id_and_priority) = InvokeIdAndPriority::parse(input)?;
+    let (input, result) = parse_get_data_result(input)?;
+    Ok((input, Self { invoke_id_and_priority, result }))
+  }
+}
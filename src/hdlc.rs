@@ -1,62 +1,114 @@
 use alloc::borrow::Cow;
 use hdlcparse::type3::HdlcFrame;
-use nom::{number::complete::u8, sequence::tuple};
 
-use crate::{DlmsDataLinkLayer, Error};
+use crate::{DlmsDataLinkLayer, DlmsDataLinkLayerHeader, Error, StreamingDataLinkLayer};
 
-enum Destination {
+const FLAG: u8 = 0x7E;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
   Unicast,
   Broadcast,
 }
 
-enum MessageType {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
   Command,
   Response,
 }
 
-#[allow(unused)]
-struct LlcHeader {
+/// The LLC header preceding the information field of an HDLC frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlcHeader {
   destination: Destination,
   message_type: MessageType,
   quality: u8,
 }
 
-fn parse_llc_header(input: &[u8]) -> Result<(&[u8], LlcHeader), Error> {
-  let (input, (dest_lsap, src_lsap, quality)) =
-    tuple::<_, _, (), _>((u8, u8, u8))(input).map_err(|_| Error::InvalidFormat)?;
-  if quality != 0x00 {
-    return Err(Error::InvalidFormat)
+impl LlcHeader {
+  pub fn destination(&self) -> Destination {
+    self.destination
   }
-  let destination = match dest_lsap {
-    0xE6 => Destination::Unicast,
-    0xFF => Destination::Broadcast,
-    _ => return Err(Error::InvalidFormat),
-  };
-  let message_type = match src_lsap {
-    0xE6 => MessageType::Command,
-    0xE7 => MessageType::Response,
-    _ => return Err(Error::InvalidFormat),
+
+  pub fn message_type(&self) -> MessageType {
+    self.message_type
+  }
+
+  /// The LLC quality octet, which is reserved and `0x00` in most profiles.
+  pub fn quality(&self) -> u8 {
+    self.quality
+  }
+}
+
+/// Parse the LLC header if the information field starts with one, otherwise return the input unchanged.
+fn parse_llc_header(input: &[u8], strict: bool) -> Result<(&[u8], Option<LlcHeader>), Error> {
+  let (destination, message_type, quality) = match *input {
+    [dest_lsap, src_lsap, quality, ..] => {
+      let destination = match dest_lsap {
+        0xE6 => Destination::Unicast,
+        0xFF => Destination::Broadcast,
+        _ => return Ok((input, None)),
+      };
+      let message_type = match src_lsap {
+        0xE6 => MessageType::Command,
+        0xE7 => MessageType::Response,
+        _ => return Ok((input, None)),
+      };
+      (destination, message_type, quality)
+    },
+    _ => return Ok((input, None)),
   };
 
-  Ok((input, LlcHeader { destination, message_type, quality }))
+  if strict && quality != 0x00 {
+    return Err(Error::InvalidFormat)
+  }
+
+  Ok((&input[3..], Some(LlcHeader { destination, message_type, quality })))
 }
 
+/// HDLC data link layer.
+///
+/// Information fields starting with an LLC header have it removed, others are passed through unchanged.
+/// By default, frames whose LLC quality octet is not `0x00` are rejected. Use
+/// `HdlcDataLinkLayer<false>` to accept any quality.
 #[derive(Debug)]
-pub enum HdlcDataLinkLayer {}
+pub enum HdlcDataLinkLayer<const STRICT_QUALITY: bool = true> {}
 
-impl<'i, 'f> DlmsDataLinkLayer<'i, &'f [HdlcFrame<'i>]> for HdlcDataLinkLayer {
+impl<'i, 'f, const STRICT_QUALITY: bool> DlmsDataLinkLayer<'i, &'f [HdlcFrame<'i>]>
+  for HdlcDataLinkLayer<STRICT_QUALITY>
+{
   fn next_frame(frames: &'f [HdlcFrame<'i>]) -> Result<(&'f [HdlcFrame<'i>], Cow<'i, [u8]>), Error> {
+    let (rest, frame, _) = Self::next_frame_with_header(frames)?;
+    Ok((rest, frame))
+  }
+
+  fn next_frame_with_segment_count(
+    frames: &'f [HdlcFrame<'i>],
+  ) -> Result<(&'f [HdlcFrame<'i>], Cow<'i, [u8]>, usize), Error> {
+    let (rest, frame, _) = Self::next_frame_with_header(frames)?;
+    Ok((rest, frame, frames.len() - rest.len()))
+  }
+}
+
+impl<'i, 'f, const STRICT_QUALITY: bool> DlmsDataLinkLayerHeader<'i, &'f [HdlcFrame<'i>]>
+  for HdlcDataLinkLayer<STRICT_QUALITY>
+{
+  type Header = Option<LlcHeader>;
+
+  fn next_frame_with_header(
+    frames: &'f [HdlcFrame<'i>],
+  ) -> Result<(&'f [HdlcFrame<'i>], Cow<'i, [u8]>, Option<LlcHeader>), Error> {
     if frames.is_empty() {
       Err(Error::Incomplete(None))
     } else if !frames[0].segmented {
       let information = frames[0].information;
-      let (information, _) = parse_llc_header(information)?;
-      Ok((&frames[1..], Cow::from(information)))
+      let (information, header) = parse_llc_header(information, STRICT_QUALITY)?;
+      Ok((&frames[1..], Cow::from(information), header))
     } else {
       let mut done = false;
       let mut len = 0;
-      let (information, _) = parse_llc_header(frames[0].information)?;
-      let mut information = information.to_owned();
+      let (information, header) = parse_llc_header(frames[0].information, STRICT_QUALITY)?;
+      let mut information = information.to_vec();
       for frame in &frames[1..] {
         information.extend(frame.information);
         len += 1;
@@ -66,10 +118,32 @@ impl<'i, 'f> DlmsDataLinkLayer<'i, &'f [HdlcFrame<'i>]> for HdlcDataLinkLayer {
         }
       }
       if done {
-        Ok((&frames[len..], Cow::from(information)))
+        Ok((&frames[(len + 1)..], Cow::from(information), header))
       } else {
         Err(Error::Incomplete(None))
       }
     }
   }
 }
+
+impl<const STRICT_QUALITY: bool> StreamingDataLinkLayer for HdlcDataLinkLayer<STRICT_QUALITY> {
+  type Frame<'i> = HdlcFrame<'i>;
+
+  fn parse_frame(input: &[u8]) -> Result<(&[u8], HdlcFrame<'_>), Error> {
+    // The closing flag is not consumed, so skip it if the next frame has its own opening flag.
+    let input = match input {
+      [FLAG, FLAG, ..] => &input[1..],
+      _ => input,
+    };
+
+    HdlcFrame::parse(input).map_err(|err| match err {
+      hdlcparse::Error::Incomplete(needed) => Error::Incomplete(needed),
+      hdlcparse::Error::InvalidChecksum => Error::ChecksumMismatch,
+      _ => Error::InvalidFormat,
+    })
+  }
+
+  fn is_frame_start(byte: u8) -> bool {
+    byte == FLAG
+  }
+}
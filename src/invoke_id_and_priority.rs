@@ -0,0 +1,50 @@
+use nom::{number::streaming::u8, IResult};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::{Priority, ServiceClass};
+
+/// Invoke ID and priority of a request or response APDU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvokeIdAndPriority(pub(crate) u8);
+
+impl InvokeIdAndPriority {
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, id) = u8(input)?;
+    Ok((input, Self(id)))
+  }
+
+  pub fn priority(&self) -> Priority {
+    if (self.0 & (1 << 7)) == 0 {
+      Priority::Normal
+    } else {
+      Priority::High
+    }
+  }
+
+  pub fn service_class(&self) -> ServiceClass {
+    if (self.0 & (1 << 6)) == 0 {
+      ServiceClass::Unconfirmed
+    } else {
+      ServiceClass::Confirmed
+    }
+  }
+
+  pub fn invoke_id(&self) -> u8 {
+    self.0 & 0x0f
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for InvokeIdAndPriority {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let mut s = serializer.serialize_struct("InvokeIdAndPriority", 3)?;
+    s.serialize_field("invoke_id", &self.invoke_id())?;
+    s.serialize_field("priority", &self.priority())?;
+    s.serialize_field("service_class", &self.service_class())?;
+    s.end()
+  }
+}
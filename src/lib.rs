@@ -1,53 +1,143 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_debug_implementations)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
-use alloc::borrow::Cow;
-use alloc::collections::btree_map::BTreeMap;
 
-use core::borrow::Borrow;
-use core::convert::TryFrom;
 use core::fmt;
-use core::mem;
 use core::num::NonZeroUsize;
-use core::ops::{Deref, DerefMut};
-
-use aes::Aes128;
-use cipher::Key;
-use nom::{
-  branch::alt,
-  combinator::{all_consuming, complete, fail},
-  multi::fold_many0,
-  number::streaming::u8,
-  Finish, IResult,
-};
-#[cfg(feature = "serde")]
-use serde::{ser::SerializeMap, Serialize, Serializer};
 
+use nom::{Finish, IResult};
+
+#[cfg(feature = "alloc")]
+mod action;
+#[cfg(feature = "alloc")]
+mod apdu;
+#[cfg(feature = "alloc")]
+pub use apdu::{parse, Apdu};
+pub mod codes;
+#[cfg(feature = "mbusparse")]
 mod control_information;
+#[cfg(feature = "alloc")]
+pub use action::{ActionRequest, ActionResponse, ActionResult};
 mod data;
+#[cfg(feature = "alloc")]
+pub mod debug;
 pub use data::*;
+#[cfg(feature = "alloc")]
+mod data_link_layer;
+#[cfg(feature = "alloc")]
+pub use data_link_layer::{DlmsDataLinkLayer, DlmsDataLinkLayerHeader};
+mod data_ref;
+pub use data_ref::DataRef;
+mod data_access_result;
+pub use data_access_result::DataAccessResult;
+#[cfg(feature = "alloc")]
 mod data_notification;
+#[cfg(feature = "alloc")]
 use data_notification::*;
+#[cfg(feature = "alloc")]
+mod dlms;
+#[cfg(feature = "alloc")]
+pub use dlms::{DecodeScratch, Dlms, Metadata};
+#[cfg(feature = "alloc")]
+mod descriptor;
+#[cfg(feature = "alloc")]
+pub use descriptor::{CosemAttributeDescriptor, CosemMethodDescriptor, SelectiveAccessDescriptor};
+#[cfg(feature = "alloc")]
+mod event_notification;
+#[cfg(feature = "alloc")]
+pub use event_notification::EventNotification;
+#[cfg(feature = "alloc")]
+mod get;
+#[cfg(feature = "alloc")]
+pub use get::{GetRequest, GetResponse};
+#[cfg(feature = "alloc")]
 mod general_glo_ciphering;
-use general_glo_ciphering::GeneralGloCiphering;
+#[cfg(feature = "alloc")]
+pub use general_glo_ciphering::GeneralGloCiphering;
+#[cfg(feature = "alloc")]
+mod invoke_id_and_priority;
+#[cfg(feature = "alloc")]
+pub use invoke_id_and_priority::InvokeIdAndPriority;
 mod obis_code;
 pub use obis_code::ObisCode;
+#[cfg(feature = "alloc")]
+mod obis_map;
+#[cfg(feature = "alloc")]
+pub use obis_map::{MergePolicy, ObisMap};
+#[cfg(feature = "alloc")]
+mod read;
+#[cfg(feature = "alloc")]
+pub use read::{ReadRequest, ReadResponse, ReadResult, VariableAccessSpecification};
+#[cfg(feature = "alloc")]
+mod register;
+#[cfg(feature = "alloc")]
+pub use register::Register;
+#[cfg(feature = "alloc")]
+mod replay;
+#[cfg(feature = "alloc")]
+pub use replay::ReplayWindow;
 mod security_control;
 pub use security_control::SecurityControl;
+#[cfg(feature = "alloc")]
+mod set;
+#[cfg(feature = "alloc")]
+pub use set::{SetRequest, SetResponse};
+#[cfg(feature = "uom")]
+mod quantity;
+#[cfg(feature = "uom")]
+pub use quantity::Quantity;
 mod unit;
-pub use unit::Unit;
+#[cfg(feature = "serde")]
+pub use unit::WithUnitFormatter;
+pub use unit::{CurrencyUnitFormatter, DefaultUnitFormatter, Unit, UnitFormatter};
+#[cfg(feature = "alloc")]
+mod decoder;
+#[cfg(feature = "alloc")]
+pub use decoder::{DlmsDecoder, StreamingDataLinkLayer};
 #[cfg(feature = "hdlcparse")]
 pub mod hdlc;
+#[cfg(feature = "hdlcparse")]
+pub use hdlc::HdlcDataLinkLayer;
 #[cfg(feature = "mbusparse")]
 pub mod mbus;
+#[cfg(feature = "mbusparse")]
+pub use mbus::MBusDataLinkLayer;
+#[cfg(feature = "alloc")]
+pub mod raw;
+#[cfg(feature = "alloc")]
+pub use raw::RawDataLinkLayer;
+#[cfg(feature = "alloc")]
+pub mod wrapper;
+#[cfg(feature = "alloc")]
+pub use wrapper::WrapperDataLinkLayer;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "std")]
+pub use reader::DlmsReader;
 
-#[derive(Debug, Clone)]
+/// An error which occurred while parsing or decrypting a frame.
+///
+/// This type is `Copy` and does not allocate, so it can be used without an allocator.
+/// New variants must keep it that way, i.e. only carry plain values rather than
+/// `String`s, `Vec`s or boxed sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
   InvalidFormat,
+  /// More input is needed, optionally with a lower bound on how much.
+  ///
+  /// The size is counted in units of the data link layer's input, i.e. bytes
+  /// for byte-oriented layers or frames for layers which take a slice of frames.
   Incomplete(Option<NonZeroUsize>),
   DecryptionFailed,
   ChecksumMismatch,
+  KeyRequired,
+  Replay,
+  /// The APDU is protected using a security suite other than the AES-GCM-128 based suites 0 and 1.
+  UnsupportedSuite(u8),
+  /// Reading the input failed, e.g. in a `DlmsReader`, which keeps the underlying error.
+  Io,
 }
 
 impl fmt::Display for Error {
@@ -57,6 +147,10 @@ impl fmt::Display for Error {
       Self::Incomplete(_) => write!(f, "incomplete"),
       Self::DecryptionFailed => write!(f, "decryption failed"),
       Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+      Self::KeyRequired => write!(f, "key required to decrypt APDU"),
+      Self::Replay => write!(f, "replayed invocation counter"),
+      Self::UnsupportedSuite(suite_id) => write!(f, "unsupported security suite {}", suite_id),
+      Self::Io => write!(f, "I/O error"),
     }
   }
 }
@@ -74,44 +168,11 @@ impl<I> nom::error::ParseError<I> for Error {
   }
 }
 
-pub trait DlmsDataLinkLayer<'i, I> {
-  fn next_frame(input: I) -> Result<(I, Cow<'i, [u8]>), Error>;
-}
-
-#[derive(Debug)]
-pub struct Dlms {
-  key: Key<Aes128>,
-}
-
-impl Dlms {
-  pub fn new(key: impl Into<Key<Aes128>>) -> Self {
-    Dlms { key: key.into() }
-  }
-
-  pub fn decrypt<'i, Dll, I>(&self, input: I) -> Result<(I, ObisMap), Error>
-  where
-    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
-  {
-    let (output, apdu) = self.decrypt_apdu::<Dll, _>(input)?;
-
-    let (_, obis) = ObisMap::parse(&apdu).map_err(|_| Error::InvalidFormat)?;
-
-    Ok((output, obis))
-  }
-
-  pub fn decrypt_apdu<'i, Dll, I>(&self, input: I) -> Result<(I, Apdu), Error>
-  where
-    Dll: DlmsDataLinkLayer<'i, I> + ?Sized,
-  {
-    let (output, frame) = Dll::next_frame(input)?;
-    let (_, apdu) =
-      map_nom_error(all_consuming(complete(|input| Apdu::parse_encrypted(input, &self.key)))(frame.borrow()))?;
-
-    Ok((output, apdu))
-  }
-}
-
-fn map_nom_error<I, O>(result: IResult<I, O, Error>) -> Result<(I, O), Error> {
+/// Convert the result of a `nom` parser into a `Result` with this crate's [`Error`].
+///
+/// `nom::Err::Incomplete` is mapped to [`Error::Incomplete`], which is useful when
+/// implementing [`DlmsDataLinkLayer`] using `nom`.
+pub fn map_nom_error<I, O>(result: IResult<I, O, Error>) -> Result<(I, O), Error> {
   result
     .map_err(|err| match err {
       nom::Err::Incomplete(needed) => nom::Err::Failure(Error::Incomplete(match needed {
@@ -123,236 +184,15 @@ fn map_nom_error<I, O>(result: IResult<I, O, Error>) -> Result<(I, O), Error> {
     .finish()
 }
 
-#[derive(Debug, Clone, PartialEq)]
-#[non_exhaustive]
-pub enum Apdu {
-  DataNotification(DataNotification),
-  GeneralGloCiphering(GeneralGloCiphering),
-}
-
-impl Apdu {
-  pub fn parse_encrypted<'i>(input: &'i [u8], key: &Key<Aes128>) -> IResult<&'i [u8], Self, Error> {
-    let (input, apdu) = Self::parse(input).map_err(|_| nom::Err::Failure(Error::InvalidFormat))?;
-
-    let apdu = match apdu {
-      Apdu::GeneralGloCiphering(ciphering) => {
-        let payload = ciphering.decrypt(key).map_err(|_| nom::Err::Failure(Error::DecryptionFailed))?;
-
-        let (_, apdu) =
-          all_consuming(complete(Apdu::parse))(&payload).map_err(|_| nom::Err::Failure(Error::InvalidFormat))?;
-        apdu
-      },
-      apdu => apdu,
-    };
-
-    Ok((input, apdu))
-  }
-
-  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
-    let (input, tag) = u8(input)?;
-    match tag {
-      15 => {
-        let (input, data_notification) = DataNotification::parse(input)?;
-        Ok((input, Self::DataNotification(data_notification)))
-      },
-      219 => {
-        let (input, general_glo_ciphering) = GeneralGloCiphering::parse(input)?;
-        Ok((input, Self::GeneralGloCiphering(general_glo_ciphering)))
-      },
-      tag => unimplemented!("parsing APDU type {}", tag),
-    }
-  }
-}
-
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Debug, Clone, PartialEq)]
-pub struct Register {
-  obis_code: ObisCode,
-  value: Data,
-  unit: Option<Unit>,
-}
-
-impl Register {
-  pub fn obis_code(&self) -> &ObisCode {
-    &self.obis_code
-  }
-
-  pub fn value(&self) -> &Data {
-    &self.value
-  }
-
-  pub fn unit(&self) -> Option<&Unit> {
-    self.unit.as_ref()
-  }
-
-  fn parse_obis_code(input: &[Data]) -> IResult<&[Data], ObisCode> {
-    if let Some(data) = input.get(0) {
-      match data {
-        Data::OctetString(obis_code) => {
-          let (_, code) = all_consuming(ObisCode::parse)(obis_code).map_err(|e| e.map_input(|_| input))?;
-          Ok((&input[1..], code))
-        },
-        _ => fail(input),
-      }
-    } else {
-      Err(nom::Err::Incomplete(nom::Needed::new(1)))
-    }
-  }
-
-  fn parse_value(input: &[Data]) -> IResult<&[Data], Data> {
-    if let Some(data) = input.get(0) {
-      Ok((&input[1..], data.clone()))
-    } else {
-      Err(nom::Err::Incomplete(nom::Needed::new(1)))
-    }
-  }
-
-  fn parse_scaler_unit(input: &[Data]) -> IResult<&[Data], (i8, u8)> {
-    if let Some(data) = input.get(0) {
-      match data {
-        Data::Structure(data) if data.len() == 2 => {
-          if let (Data::Integer(ref scaler), Data::Enum(ref unit)) = (&data[0], &data[1]) {
-            if *scaler != 0x00 || *unit != 0xff {
-              return Ok((&input[1..], (*scaler, *unit)))
-            }
-          }
-        },
-        _ => (),
-      }
-
-      fail(input)
-    } else {
-      Err(nom::Err::Incomplete(nom::Needed::new(1)))
-    }
-  }
-
-  fn parse_inner_nested(input: &[Data]) -> IResult<&[Data], (ObisCode, Data, Option<Unit>)> {
-    if let Some(data) = input.get(0) {
-      if let Data::Structure(ref data) = data {
-        let (_, inner) = complete(Self::parse_inner)(data)?;
-        return Ok((&input[1..], inner))
-      }
-
-      fail(input)
-    } else {
-      Err(nom::Err::Incomplete(nom::Needed::new(1)))
-    }
-  }
-
-  fn parse_inner(input: &[Data]) -> IResult<&[Data], (ObisCode, Data, Option<Unit>)> {
-    let (input, obis_code) = Self::parse_obis_code(input)?;
-    let (input, mut value) = Self::parse_value(input)?;
-
-    let (input, unit) = if let Ok((input, (scaler, unit))) = Self::parse_scaler_unit(input) {
-      macro_rules! scale {
-        ($value:expr, $scaler:expr, $ty:ident) => {{
-          let factor = (0..($scaler.abs() as usize)).fold(1, |f, _| f * 10);
-
-          if $scaler < 0 {
-            $value as $ty / factor as $ty
-          } else {
-            $value as $ty * factor as $ty
-          }
-        }};
-      }
-
-      value = match value {
-        Data::LongUnsigned(value) => Data::Float32(scale!(value, scaler, f32)),
-        Data::DoubleLongUnsigned(value) => Data::Float64(scale!(value, scaler, f64)),
-        value => value,
-      };
-
-      let unit = match Unit::try_from(unit) {
-        Ok(unit) => unit,
-        Err(_) => return fail(input),
-      };
-
-      (input, Some(unit))
-    } else {
-      (input, None)
-    };
-
-    Ok((input, (obis_code, value, unit)))
-  }
-
-  fn parse(input: &[Data]) -> IResult<&[Data], Self> {
-    let (input, (obis_code, value, unit)) =
-      alt((complete(Self::parse_inner), complete(Self::parse_inner_nested)))(input)?;
-
-    Ok((input, Self { obis_code, value, unit }))
-  }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct ObisMap {
-  map: BTreeMap<ObisCode, Register>,
-}
-
-impl Deref for ObisMap {
-  type Target = BTreeMap<ObisCode, Register>;
-
-  fn deref(&self) -> &Self::Target {
-    &self.map
-  }
-}
-
-impl DerefMut for ObisMap {
-  fn deref_mut(&mut self) -> &mut Self::Target {
-    &mut self.map
-  }
-}
-
-impl ObisMap {
-  /// Convert the `Data` for a given `ObisCode` using the given function.
-  pub fn convert(&mut self, code: &ObisCode, mut f: impl FnMut(Data) -> Data) {
-    if let Some(reg) = self.map.get_mut(code) {
-      let value = &mut reg.value;
-      *value = f(mem::replace(value, Data::Null));
-    }
-  }
-
-  pub fn parse(input: &Apdu) -> IResult<(), Self> {
-    let data = match input {
-      Apdu::DataNotification(DataNotification { notification_body: Data::Structure(data), .. }) => data.as_slice(),
-      _ => return fail(()),
-    };
-
-    let (_, values) = all_consuming(fold_many0(Register::parse, BTreeMap::new, |mut values, reg| {
-      values.insert(reg.obis_code.clone(), reg);
-      values
-    }))(data)
-    .map_err(|e| e.map_input(|_| ()))?;
-
-    Ok(((), Self { map: values }))
-  }
-}
-
-#[cfg(feature = "serde")]
-impl Serialize for ObisMap {
-  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-  where
-    S: Serializer,
-  {
-    #[derive(Serialize)]
-    struct Entry<'a> {
-      value: &'a Data,
-      #[serde(skip_serializing_if = "Option::is_none")]
-      unit: Option<&'a str>,
-    }
-
-    let mut map = serializer.serialize_map(Some(self.map.len()))?;
-    for (k, v) in self.map.iter() {
-      map.serialize_entry(k, &Entry { value: v.value(), unit: v.unit().and_then(|u| u.as_str()) })?;
-    }
-    map.end()
-  }
-}
+#[cfg(all(test, feature = "alloc"))]
+mod fixtures;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod test {
   use super::*;
 
-  use alloc::vec;
+  use alloc::{string::ToString, vec, vec::Vec};
+  use core::convert::TryFrom;
 
   #[test]
   fn parse_apdu() {
@@ -408,12 +248,12 @@ mod test {
       Apdu::parse(&payload).unwrap().1,
       Apdu::DataNotification(DataNotification {
         long_invoke_id_and_priority: LongInvokeIdAndPriority(21817),
-        date_time: DateTime {
+        date_time: Some(DateTime {
           date: Date { year: 2016, month: 9, day_of_month: 8, day_of_week: 4 },
           time: Time { hour: Some(19), minute: Some(13), second: Some(25), hundredth: Some(0) },
           offset_minutes: Some(-60),
           clock_status: Some(ClockStatus(128)),
-        },
+        }),
         notification_body: Data::Structure(vec![
           Data::OctetString(vec![7, 224, 9, 8, 4, 19, 13, 25, 0, 0, 0, 128]),
           Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
@@ -427,62 +267,1552 @@ mod test {
     );
   }
 
-  const KEY: [u8; 16] = 0xdeafbeefcafebabedeafbeefcafebabeu128.to_be_bytes();
+  #[test]
+  fn parse_long_octet_string() {
+    let mut input = vec![0x09, 0x82, 0x01, 0x2c];
+    input.extend((0..300).map(|i| i as u8));
+    input.push(0x00);
 
-  #[rustfmt::skip]
-  const ENCRYPTED_MESSAGE: [u8; 354] = [
-    0xdb, // Tag
-    0x08, 0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9, // System Title
-    0x82, 0x01, 0x55, // Payload Length
-    0x21, // Security Control
-    0x00, 0x02, 0xbc, 0x66, // Invocation Counter
-    0xf4, 0x50, 0xb5, 0x97, 0xb1, 0x1f, 0x09, 0x45, 0x0a, 0x68, 0x03, 0x63, 0xe7, 0x18, 0x41, 0xc4,
-    0x09, 0x82, 0x9a, 0xab, 0xe0, 0x8b, 0x44, 0x3f, 0x6c, 0x9a, 0x70, 0x73, 0xbc, 0xc4, 0x5c, 0xdb,
-    0x8b, 0x57, 0x48, 0x85, 0x11, 0x80, 0x42, 0x0c, 0x79, 0xd9, 0x0e, 0x26, 0xf1, 0x26, 0x15, 0xbe,
-    0xed, 0x5f, 0xea, 0x7d, 0xc8, 0x54, 0x26, 0xaf, 0x38, 0x9c, 0x8c, 0x92, 0x02, 0x9f, 0xf3, 0x64,
-    0x63, 0xf7, 0xbf, 0x1b, 0x9e, 0x56, 0xa3, 0x88, 0x75, 0x69, 0xf6, 0x1a, 0x5a, 0x86, 0x23, 0x9a,
-    0xd6, 0x2f, 0xda, 0x85, 0x48, 0xb3, 0xf6, 0x22, 0x61, 0x25, 0x3f, 0xe5, 0xcd, 0x0e, 0x06, 0xb7,
-    0x14, 0xad, 0x5c, 0x26, 0x85, 0xc8, 0x45, 0x57, 0x70, 0x8d, 0x57, 0xde, 0xba, 0x10, 0xca, 0xc0,
-    0x8d, 0xeb, 0xba, 0xcc, 0xc5, 0x66, 0x2b, 0x45, 0x50, 0x14, 0xbc, 0x8b, 0x44, 0x17, 0x48, 0x1d,
-    0x2b, 0x9a, 0xf1, 0x66, 0x22, 0x07, 0x1f, 0xbe, 0xef, 0x5e, 0xce, 0xaf, 0x1e, 0x39, 0xf7, 0x99,
-    0x6c, 0xa9, 0x98, 0x27, 0x68, 0x31, 0xe6, 0x84, 0xe0, 0x70, 0x44, 0x57, 0xd4, 0xcd, 0x64, 0x96,
-    0xca, 0xd4, 0xdb, 0xd9, 0x03, 0x35, 0x98, 0x11, 0x13, 0x5e, 0x7e, 0x70, 0xb4, 0x06, 0x30, 0x4c,
-    0x8e, 0x7e, 0xce, 0x20, 0x90, 0xcd, 0x74, 0x3a, 0x08, 0x2d, 0xa6, 0x2e, 0xd6, 0x20, 0x83, 0xb3,
-    0xd3, 0xf1, 0x21, 0xf9, 0x97, 0x2d, 0xd6, 0x48, 0x78, 0x86, 0xf6, 0xaf, 0x2c, 0x5c, 0x76, 0x39,
-    0x81, 0xa2, 0xe1, 0xa1, 0x28, 0x3c, 0x52, 0x12, 0xa8, 0x15, 0x77, 0x84, 0x7d, 0x40, 0xf7, 0x64,
-    0xba, 0x93, 0x6d, 0x26, 0xc6, 0x33, 0xec, 0x73, 0xb0, 0x1b, 0xc7, 0x1a, 0xfd, 0x6d, 0x4c, 0x10,
-    0xbb, 0xcb, 0xea, 0x96, 0x86, 0xf0, 0x3d, 0x40, 0x84, 0x99, 0xee, 0x7f, 0x16, 0x35, 0x69, 0xea,
-    0x7d, 0xb6, 0xf5, 0x23, 0xea, 0xbd, 0xfe, 0x5d, 0x31, 0xb5, 0xb2, 0x34, 0xf3, 0x09, 0xc5, 0x71,
-    0xbc, 0xec, 0x4f, 0x3f, 0xae, 0x4c, 0xe9, 0xab, 0xce, 0x92, 0x62, 0x4a, 0x37, 0xeb, 0x62, 0x0d,
-    0x2c, 0x2a, 0xdd, 0xf6, 0x0c, 0xd5, 0xaa, 0x65, 0xd1, 0xe2, 0xe4, 0x5c, 0xe2, 0x13, 0x4f, 0x0e,
-    0x4c, 0x2f, 0x70, 0xe1, 0x9d, 0x93, 0x6f, 0x84, 0x5c, 0x6f, 0x36, 0x91, 0xb3, 0x26, 0x00, 0x5d,
-    0x43, 0x9c, 0xe6, 0x46, 0x27, 0x53, 0x92, 0xf6, 0x0b, 0x3b, 0x69, 0x90, 0x3f, 0x82, 0x84, 0x78,
-  ];
+    let (rest, data) = Data::parse(&input).unwrap();
+    assert_eq!(data, Data::OctetString((0..300).map(|i| i as u8).collect()));
+    assert_eq!(rest, [0x00]);
+  }
 
-  #[rustfmt::skip]
-  const DECRYPTED_MESSAGE: [u8; 336] = [
-    0x0f, 0x00, 0x02, 0xb5, 0xe4, 0x0c, 0x07, 0xe5, 0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0x00, 0xff,
-    0x88, 0x80, 0x02, 0x10, 0x09, 0x06, 0x00, 0x00, 0x01, 0x00, 0x00, 0xff, 0x09, 0x0c, 0x07, 0xe5,
-    0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0x00, 0xff, 0x88, 0x80, 0x02, 0x02, 0x09, 0x06, 0x00, 0x00,
-    0x60, 0x01, 0x00, 0xff, 0x09, 0x0e, 0x31, 0x4b, 0x46, 0x4d, 0x30, 0x32, 0x30, 0x30, 0x30, 0x37,
-    0x30, 0x33, 0x31, 0x33, 0x02, 0x02, 0x09, 0x06, 0x00, 0x00, 0x2a, 0x00, 0x00, 0xff, 0x09, 0x10,
-    0x4b, 0x46, 0x4d, 0x31, 0x32, 0x30, 0x30, 0x32, 0x30, 0x30, 0x30, 0x37, 0x30, 0x33, 0x31, 0x33,
-    0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x20, 0x07, 0x00, 0xff, 0x12, 0x09, 0x20, 0x02, 0x02, 0x0f,
-    0xff, 0x16, 0x23, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x34, 0x07, 0x00, 0xff, 0x12, 0x09, 0x04,
-    0x02, 0x02, 0x0f, 0xff, 0x16, 0x23, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x48, 0x07, 0x00, 0xff,
-    0x12, 0x09, 0x0d, 0x02, 0x02, 0x0f, 0xff, 0x16, 0x23, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x1f,
-    0x07, 0x00, 0xff, 0x12, 0x01, 0x6d, 0x02, 0x02, 0x0f, 0xfe, 0x16, 0x21, 0x02, 0x03, 0x09, 0x06,
-    0x01, 0x00, 0x33, 0x07, 0x00, 0xff, 0x12, 0x03, 0x0e, 0x02, 0x02, 0x0f, 0xfe, 0x16, 0x21, 0x02,
-    0x03, 0x09, 0x06, 0x01, 0x00, 0x47, 0x07, 0x00, 0xff, 0x12, 0x02, 0x62, 0x02, 0x02, 0x0f, 0xfe,
-    0x16, 0x21, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x01, 0x07, 0x00, 0xff, 0x06, 0x00, 0x00, 0x0f,
-    0x5e, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x1b, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x02, 0x07, 0x00,
-    0xff, 0x06, 0x00, 0x00, 0x00, 0x00, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x1b, 0x02, 0x03, 0x09, 0x06,
-    0x01, 0x00, 0x01, 0x08, 0x00, 0xff, 0x06, 0x00, 0x51, 0x00, 0x15, 0x02, 0x02, 0x0f, 0x00, 0x16,
-    0x1e, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x02, 0x08, 0x00, 0xff, 0x06, 0x00, 0x00, 0x00, 0x00,
-    0x02, 0x02, 0x0f, 0x00, 0x16, 0x1e, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x03, 0x08, 0x00, 0xff,
-    0x06, 0x00, 0x00, 0x34, 0x65, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x20, 0x02, 0x03, 0x09, 0x06, 0x01,
-    0x00, 0x04, 0x08, 0x00, 0xff, 0x06, 0x00, 0x08, 0xa3, 0xbc, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x20,
-  ];
+  #[test]
+  fn parse_compact_array_of_structures() {
+    #[rustfmt::skip]
+    let input = [
+      0x13, // Type (Compact Array)
+        0x02, 0x02, // Type Description (Structure, Length)
+          0x06, // Type (Double Long Unsigned)
+          0x12, // Type (Long Unsigned)
+        0x0c, // Length
+          0x00, 0x00, 0x00, 0x01, 0x00, 0x02,
+          0x00, 0x00, 0x00, 0x03, 0x00, 0x04,
+    ];
+
+    let (rest, data) = Data::parse(&input).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(
+      data,
+      Data::Array(vec![
+        Data::Structure(vec![Data::DoubleLongUnsigned(1), Data::LongUnsigned(2)]),
+        Data::Structure(vec![Data::DoubleLongUnsigned(3), Data::LongUnsigned(4)]),
+      ])
+    );
+  }
+
+  #[test]
+  fn encode_fixture_data() {
+    #[rustfmt::skip]
+    let items: [&[u8]; 6] = [
+      &[0x19, 0x07, 0xe5, 0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0xff, 0xff, 0x88, 0x80], // Date Time
+      &[0x1a, 0x07, 0xe5, 0x09, 0x0b, 0xff], // Date
+      &[0x1b, 0x09, 0x0d, 0xff, 0xff], // Time
+      &[0x0d, 0x42], // BCD
+      &[0x04, 0x0a, 0xff, 0xc0], // Bit String
+      &[0x1e, 0xff, 0xff, 0xff, 0xfe], // Delta Double Long
+    ];
+
+    for bytes in items {
+      let (_, data) = Data::parse(bytes).unwrap();
+      let mut encoded = Vec::new();
+      fixtures::data(&data, &mut encoded);
+      assert_eq!(encoded, bytes, "{:?}", data);
+    }
+  }
+
+  #[test]
+  fn parse_delta_encoded_compact_array() {
+    #[rustfmt::skip]
+    let input = [
+      0x13, // Type (Compact Array)
+        0x1f, // Type Description (Delta Unsigned)
+        0x03, // Length
+          0x05, 0x00, 0x0c,
+    ];
+
+    let (rest, deltas) = Data::parse(&input).unwrap();
+    assert!(rest.is_empty());
+
+    let deltas = match deltas {
+      Data::Array(deltas) => deltas,
+      data => panic!("unexpected data: {:?}", data),
+    };
+    assert_eq!(deltas, [Data::DeltaUnsigned(5), Data::DeltaUnsigned(0), Data::DeltaUnsigned(12)]);
+    assert_eq!(DataRef::parse(&[0x1f, 0x05]).unwrap().1, DataRef::DeltaUnsigned(5));
+    assert_eq!(Data::Unsigned(5).kind(), DataType::Unsigned);
+    assert_eq!(deltas[0].kind(), DataType::DeltaUnsigned);
+
+    assert_eq!(
+      Data::DoubleLongUnsigned(u32::MAX - 20).accumulate_deltas(&deltas),
+      Some(vec![
+        Data::DoubleLongUnsigned(u32::MAX - 15),
+        Data::DoubleLongUnsigned(u32::MAX - 15),
+        Data::DoubleLongUnsigned(u32::MAX - 3),
+      ])
+    );
+    assert_eq!(Data::DoubleLongUnsigned(u32::MAX - 10).accumulate_deltas(&deltas), None);
+  }
+
+  #[test]
+  fn unit_as_str() {
+    for unit in (0..=u8::MAX).filter_map(|n| Unit::try_from(n).ok()) {
+      assert_eq!(unit.as_str().is_some(), unit != Unit::Other, "{:?}", unit);
+    }
+
+    assert_eq!(Unit::Count.as_str(), Some(""));
+  }
+
+  #[test]
+  fn data_type_tag_round_trip() {
+    for &data_type in DataType::all() {
+      assert_eq!(DataType::try_from(data_type as u8), Ok(data_type));
+    }
+
+    assert!(DataType::all().windows(2).all(|w| w[0].to_tag() < w[1].to_tag()));
+  }
+
+  #[test]
+  fn parse_register_with_unscaled_marker() {
+    let data = [
+      Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
+      Data::DoubleLongUnsigned(1234),
+      Data::Structure(vec![Data::Integer(i8::MIN), Data::Enum(30)]),
+    ];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.value(), &Data::DoubleLongUnsigned(1234));
+    assert_eq!(register.unit(), Some(&Unit::WattHour));
+  }
+
+  #[test]
+  fn register_with_unrepresentable_scaler() {
+    let obis_code = ObisCode::new(1, 0, 1, 8, 0, 255);
+
+    let register = Register::with_scaler(obis_code, Data::LongUnsigned(5), -100, Unit::WattHour);
+    assert_eq!(register.value(), &Data::LongUnsigned(5));
+    assert_eq!(register.raw_value(), &Data::LongUnsigned(5));
+    assert_eq!(register.scaler(), Some(-100));
+
+    let register = Register::with_scaler(obis_code, Data::LongUnsigned(5), 100, Unit::WattHour);
+    assert_eq!(register.value(), &Data::LongUnsigned(5));
+  }
+
+  #[test]
+  fn parse_register_with_padded_obis_code() {
+    let data = [Data::OctetString(vec![1, 0, 1, 8, 0, 255, 0, 0]), Data::DoubleLongUnsigned(1234)];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.obis_code(), &ObisCode::new(1, 0, 1, 8, 0, 255));
+  }
+
+  #[test]
+  fn parse_register_with_unknown_unit() {
+    let data = [
+      Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
+      Data::DoubleLongUnsigned(1234),
+      Data::Structure(vec![Data::Integer(0), Data::Enum(200)]),
+    ];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.value(), &Data::Float64(1234.0));
+    assert_eq!(register.unit(), None);
+    assert_eq!(register.unit_code(), Some(200));
+  }
+
+  #[test]
+  fn parse_register_with_long_scaler() {
+    let data = [
+      Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
+      Data::DoubleLongUnsigned(1234),
+      Data::Structure(vec![Data::Long(-1), Data::Enum(30)]),
+    ];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.value(), &Data::Float64(123.4));
+    assert_eq!(register.scaler(), Some(-1));
+    assert_eq!(register.unit(), Some(&Unit::WattHour));
+  }
+
+  #[test]
+  fn parse_register_with_scaler_unit_before_value() {
+    let data = [
+      Data::OctetString(vec![8, 0, 1, 0, 0, 255]),
+      Data::Structure(vec![Data::Integer(-3), Data::Enum(13)]),
+      Data::DoubleLongUnsigned(1234),
+      Data::OctetString(vec![0, 0, 96, 1, 0, 255]),
+      Data::OctetString(b"12345678".to_vec()),
+    ];
+    let (rest, register) = Register::parse(&data).unwrap();
+    assert_eq!(rest.len(), 2);
+    assert_eq!(register.value(), &Data::Float64(1.234));
+    assert_eq!(register.unit(), Some(&Unit::CubicMeter));
+  }
+
+  #[test]
+  fn parse_register_with_value_resembling_scaler_unit() {
+    let value = Data::Structure(vec![Data::Integer(5), Data::Enum(1)]);
+
+    let data = [
+      Data::OctetString(vec![0, 0, 96, 14, 0, 255]),
+      value.clone(),
+      Data::Structure(vec![Data::Integer(-1), Data::Enum(30)]),
+    ];
+    let (rest, register) = Register::parse(&data).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(register.value(), &value);
+    assert_eq!(register.scaler(), Some(-1));
+    assert_eq!(register.unit(), Some(&Unit::WattHour));
+
+    let data = [
+      Data::OctetString(vec![0, 0, 96, 14, 0, 255]),
+      value.clone(),
+      Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
+      Data::DoubleLongUnsigned(1234),
+    ];
+    let (rest, register) = Register::parse(&data).unwrap();
+    assert_eq!(rest.len(), 2);
+    assert_eq!(register.value(), &value);
+    assert_eq!(register.unit(), None);
+  }
+
+  #[test]
+  fn parse_register_with_large_scaler() {
+    let data = [
+      Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
+      Data::DoubleLongUnsigned(5),
+      Data::Structure(vec![Data::Integer(12), Data::Enum(30)]),
+    ];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.value(), &Data::Float64(5e12));
+  }
+
+  #[test]
+  fn register_new() {
+    let obis_code = ObisCode::new(1, 0, 1, 8, 0, 255);
+    let register = Register::new(obis_code, Data::DoubleLongUnsigned(1234), Some(Unit::WattHour));
+    assert_eq!(register.obis_code(), &obis_code);
+    assert_eq!(register.value(), &Data::DoubleLongUnsigned(1234));
+    assert_eq!(register.raw_value(), &Data::DoubleLongUnsigned(1234));
+    assert_eq!(register.scaler(), None);
+    assert_eq!(register.unit(), Some(&Unit::WattHour));
+    assert_eq!(register.unit_code(), Some(Unit::WattHour as u8));
+
+    let data = [Data::OctetString(vec![1, 0, 1, 8, 0, 255]), Data::DoubleLongUnsigned(1234)];
+    let (_, parsed) = Register::parse(&data).unwrap();
+    assert_eq!(parsed, Register::new(obis_code, Data::DoubleLongUnsigned(1234), None));
+  }
+
+  #[test]
+  fn parse_register_with_signed_value() {
+    let data = [
+      Data::OctetString(vec![1, 0, 4, 7, 0, 255]),
+      Data::DoubleLong(-12345),
+      Data::Structure(vec![Data::Integer(-3), Data::Enum(27)]),
+    ];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.value(), &Data::Float64(-12.345));
+    assert_eq!(register.raw_value(), &Data::DoubleLong(-12345));
+
+    let data = [
+      Data::OctetString(vec![0, 0, 96, 9, 0, 255]),
+      Data::Long(-125),
+      Data::Structure(vec![Data::Integer(-1), Data::Enum(9)]),
+    ];
+    let (_, register) = Register::parse(&data).unwrap();
+    assert_eq!(register.value(), &Data::Float32(-12.5));
+  }
+
+  #[cfg(feature = "uom")]
+  #[test]
+  fn register_quantity() {
+    use uom::si::power::kilowatt;
+
+    let power = ObisCode::new(1, 0, 1, 7, 0, 255);
+    let register = Register::with_scaler(power, Data::DoubleLongUnsigned(1234), 1, Unit::Watt);
+    match register.quantity() {
+      Some(Quantity::Power(power)) => assert_eq!(power.get::<kilowatt>(), 12.34),
+      quantity => panic!("unexpected quantity: {:?}", quantity),
+    }
+
+    let register = Register::new(power, Data::DoubleLongUnsigned(1234), Some(Unit::Var));
+    assert_eq!(register.quantity(), None);
+  }
+
+  #[test]
+  fn obis_map_from_registers() {
+    let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+    let power = ObisCode::new(1, 0, 1, 7, 0, 255);
+
+    let map = vec![
+      Register::new(energy, Data::Float64(1.0), Some(Unit::WattHour)),
+      Register::new(power, Data::Float32(2.0), Some(Unit::Watt)),
+      Register::new(energy, Data::Float64(3.0), Some(Unit::WattHour)),
+    ]
+    .into_iter()
+    .collect::<ObisMap>();
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(3.0)));
+    assert_eq!(map.get(&power).map(|reg| reg.value()), Some(&Data::Float32(2.0)));
+  }
+
+  #[test]
+  fn obis_map_to_vec() {
+    let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+    let power = ObisCode::new(1, 0, 1, 7, 0, 255);
+
+    let map = vec![
+      Register::new(energy, Data::Float64(1.0), Some(Unit::WattHour)),
+      Register::new(power, Data::Float32(2.0), Some(Unit::Watt)),
+    ]
+    .into_iter()
+    .collect::<ObisMap>();
+
+    let registers = map.to_vec();
+    assert_eq!(registers.iter().map(|reg| *reg.obis_code()).collect::<Vec<_>>(), [power, energy]);
+    assert_eq!(map.into_vec(), registers);
+  }
+
+  #[test]
+  fn merge_obis_maps() {
+    let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+    let power = ObisCode::new(1, 0, 1, 7, 0, 255);
+
+    let date_time = |second| DateTime {
+      date: Date { year: 2021, month: 5, day_of_month: 1, day_of_week: 6 },
+      time: Time { hour: Some(12), minute: Some(0), second: Some(second), hundredth: None },
+      offset_minutes: None,
+      clock_status: None,
+    };
+    let map = |value, second| {
+      let mut map = ObisMap { date_time: Some(date_time(second)), ..ObisMap::new() };
+      map.insert(Register::new(energy, Data::Float64(value), None));
+      map
+    };
+
+    let mut merged = map(2.0, 10);
+    let mut older = map(1.0, 5);
+    older.insert(Register::new(power, Data::Float32(3.0), None));
+    merged.merge(older, MergePolicy::KeepNewest);
+    assert_eq!(merged.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(2.0)));
+    assert_eq!(merged.get(&power).map(|reg| reg.value()), Some(&Data::Float32(3.0)));
+
+    merged.merge(map(4.0, 20), MergePolicy::KeepNewest);
+    assert_eq!(merged.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(4.0)));
+    assert_eq!(merged.date_time(), Some(&date_time(20)));
+
+    merged.merge(map(5.0, 0), MergePolicy::KeepExisting);
+    assert_eq!(merged.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(4.0)));
+
+    merged.merge(map(5.0, 0), MergePolicy::Overwrite);
+    assert_eq!(merged.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(5.0)));
+  }
+
+  #[test]
+  fn merge_obis_maps_keeps_newest_date_time() {
+    let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+
+    let date_time = |second| DateTime {
+      date: Date { year: 2021, month: 5, day_of_month: 1, day_of_week: 6 },
+      time: Time { hour: Some(12), minute: Some(0), second: Some(second), hundredth: None },
+      offset_minutes: None,
+      clock_status: None,
+    };
+    let map = |value, second| {
+      let mut map = ObisMap { date_time: Some(date_time(second)), ..ObisMap::new() };
+      map.insert(Register::new(energy, Data::Float64(value), None));
+      map
+    };
+
+    let mut merged = map(1.0, 10);
+    merged.merge(map(2.0, 5), MergePolicy::Overwrite);
+    assert_eq!(merged.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(2.0)));
+    assert_eq!(merged.date_time(), Some(&date_time(10)));
+
+    merged.merge(map(3.0, 20), MergePolicy::KeepExisting);
+    assert_eq!(merged.get(&energy).map(|reg| reg.value()), Some(&Data::Float64(2.0)));
+    assert_eq!(merged.date_time(), Some(&date_time(20)));
+
+    merged.merge(ObisMap::new(), MergePolicy::Overwrite);
+    assert_eq!(merged.date_time(), Some(&date_time(20)));
+  }
+
+  #[test]
+  fn parse_nested_registers() {
+    let register = |c| {
+      Data::Structure(vec![
+        Data::OctetString(vec![1, 0, c, 7, 0, 255]),
+        Data::LongUnsigned(2300),
+        Data::Structure(vec![Data::Integer(-1), Data::Enum(35)]),
+      ])
+    };
+
+    let apdu = Apdu::DataNotification(DataNotification {
+      long_invoke_id_and_priority: LongInvokeIdAndPriority(0),
+      date_time: None,
+      notification_body: Data::Structure(vec![
+        Data::OctetString(vec![0, 0, 96, 1, 0, 255]),
+        Data::OctetString(b"12345678".to_vec()),
+        Data::Structure(vec![register(32), Data::Structure(vec![register(52), register(72)])]),
+      ]),
+    });
+
+    let (_, obis_map) = ObisMap::parse(&apdu).unwrap();
+    assert_eq!(obis_map.len(), 4);
+    assert_eq!(obis_map[&ObisCode::new(1, 0, 72, 7, 0, 255)].value(), &Data::Float32(230.0));
+  }
+
+  #[test]
+  fn parse_registers_in_arrays() {
+    let register = |c| {
+      Data::Structure(vec![
+        Data::OctetString(vec![1, 0, c, 7, 0, 255]),
+        Data::LongUnsigned(2300),
+        Data::Structure(vec![Data::Integer(-1), Data::Enum(35)]),
+      ])
+    };
+
+    let apdu = Apdu::DataNotification(DataNotification {
+      long_invoke_id_and_priority: LongInvokeIdAndPriority(0),
+      date_time: None,
+      notification_body: Data::Array(vec![
+        register(32),
+        Data::Array(vec![register(52), register(72)]),
+        Data::Structure(vec![Data::Array(vec![register(31)])]),
+      ]),
+    });
+
+    let (_, obis_map) = ObisMap::parse(&apdu).unwrap();
+    assert_eq!(obis_map.len(), 4);
+    assert_eq!(obis_map[&ObisCode::new(1, 0, 52, 7, 0, 255)].value(), &Data::Float32(230.0));
+    assert_eq!(obis_map[&ObisCode::new(1, 0, 31, 7, 0, 255)].value(), &Data::Float32(230.0));
+  }
+
+  #[test]
+  fn parse_registers_with_data_access_result() {
+    let apdu = Apdu::DataNotification(DataNotification {
+      long_invoke_id_and_priority: LongInvokeIdAndPriority(0),
+      date_time: None,
+      notification_body: Data::Structure(vec![
+        Data::OctetString(vec![1, 0, 1, 8, 0, 255]),
+        Data::DoubleLongUnsigned(1234),
+        Data::Enum(DataAccessResult::ObjectUndefined as u8),
+        Data::OctetString(vec![1, 0, 2, 8, 0, 255]),
+        Data::DoubleLongUnsigned(5678),
+      ]),
+    });
+
+    let (_, obis_map) = ObisMap::parse(&apdu).unwrap();
+    assert_eq!(obis_map.len(), 2);
+  }
+
+  #[test]
+  fn parse_registers_from_empty_notification() {
+    for notification_body in [Data::Null, Data::Structure(vec![])] {
+      let apdu = Apdu::DataNotification(DataNotification {
+        long_invoke_id_and_priority: LongInvokeIdAndPriority(0),
+        date_time: None,
+        notification_body,
+      });
+
+      let (_, obis_map) = ObisMap::parse(&apdu).unwrap();
+      assert!(obis_map.is_empty());
+    }
+  }
+
+  #[test]
+  fn parse_date_time_forms() {
+    let date_time = [0x07, 0xe5, 0x09, 0x08, 0x03, 0x13, 0x0d, 0x19, 0x00, 0x00, 0x00, 0x80];
+
+    // A `date-time` has no length prefix, while an `octet-string` does.
+    let bytes = [&[0x02, 0x02, 0x19][..], &date_time, &[0x09, 0x0c], &date_time].concat();
+    let (rest, data) = Data::parse(&bytes).unwrap();
+    assert!(rest.is_empty());
+
+    let items = match data {
+      Data::Structure(items) => items,
+      data => panic!("unexpected data: {:?}", data),
+    };
+    assert!(matches!(items[0], Data::DateTime(_)));
+    assert!(matches!(items[1], Data::OctetString(_)));
+    assert_eq!(items[0].as_date_time(), items[1].as_date_time());
+    assert_eq!(items[0].as_date_time().unwrap().to_string(), "2021-09-08T19:13:25.00-00:00");
+
+    // The date-time of a data notification is always an octet string.
+    let notification =
+      |date_time: &[u8]| [&[0x0f, 0x00, 0x00, 0x00, 0x01, date_time.len() as u8][..], date_time, &[0x00]].concat();
+    let (_, apdu) = Apdu::parse(&notification(&date_time)).unwrap();
+    match apdu {
+      Apdu::DataNotification(notification) => {
+        assert_eq!(notification.date_time().copied(), items[0].as_date_time())
+      },
+      apdu => panic!("unexpected APDU: {:?}", apdu),
+    }
+    assert!(Apdu::parse(&notification(&[&date_time[..], &[0x00]].concat())).is_err());
+  }
+
+  #[test]
+  fn parse_apdu_without_date_time() {
+    #[rustfmt::skip]
+    let payload: [u8; 9] = [
+      0x0F, // Type (Data Notification)
+        0x00, 0x00, 0x55, 0x39, // Invoke ID & Priority
+        0x00, // Date & Time (Length)
+        0x02, // Type (Structure)
+          0x01, // Length
+            0x00, // Type (Null)
+    ];
+
+    let apdu = Apdu::parse(&payload).unwrap().1;
+    match apdu {
+      Apdu::DataNotification(ref data_notification) => assert_eq!(data_notification.date_time(), None),
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn parse_apdu_with_long_form_date_time_length() {
+    #[rustfmt::skip]
+    let payload: [u8; 22] = [
+      0x0F, // Type (Data Notification)
+        0x00, 0x00, 0x55, 0x39, // Invoke ID & Priority
+        0x81, 0x0C, // Date & Time (Length, Long Form)
+          0x07, 0xE0, 0x09, 0x08, 0x04, 0x13, 0x0D, 0x19, 0x00, 0xFF, 0xC4, 0x80, // Date & Time
+        0x02, // Type (Structure)
+          0x01, // Length
+            0x00, // Type (Null)
+    ];
+
+    let mut short_form = payload.to_vec();
+    short_form.remove(5);
+
+    let (rest, apdu) = Apdu::parse(&payload).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(apdu, Apdu::parse(&short_form).unwrap().1);
+    match apdu {
+      Apdu::DataNotification(ref data_notification) => assert!(data_notification.date_time().is_some()),
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn dlms_debug_redacts_key() {
+    let dlms = Dlms::new(KEY);
+    let debug = alloc::format!("{:?}", dlms);
+
+    assert!(debug.contains("Key(<redacted>)"));
+    assert!(!debug.contains("222"));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialize_register() {
+    let register =
+      Register::with_scaler(ObisCode::new(1, 0, 1, 8, 0, 255), Data::DoubleLongUnsigned(1234), -1, Unit::WattHour);
+
+    assert_eq!(
+      serde_json::to_value(register).unwrap(),
+      serde_json::json!({
+        "obis_code": "1-0:1.8.0*255",
+        "value": 123.4,
+        "raw_value": 1234,
+        "scaler": -1,
+        "unit": "Wh",
+        "unit_code": 30,
+      })
+    );
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialize_with_unit_formatter() {
+    struct Kilo;
+
+    impl UnitFormatter for Kilo {
+      fn format(&self, unit: Unit) -> Option<&str> {
+        match unit {
+          Unit::WattHour => Some("kWh"),
+          unit => unit.as_str(),
+        }
+      }
+    }
+
+    let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+    let register = Register::with_scaler(energy, Data::DoubleLongUnsigned(1234), 0, Unit::WattHour);
+    let map = vec![register.clone()].into_iter().collect::<ObisMap>();
+
+    assert_eq!(serde_json::to_value(register.with_unit_formatter(&Kilo)).unwrap()["unit"], "kWh");
+    assert_eq!(serde_json::to_value(map.with_unit_formatter(&Kilo)).unwrap()["1-0:1.8.0*255"]["unit"], "kWh");
+    assert_eq!(serde_json::to_value(&map).unwrap()["1-0:1.8.0*255"]["unit"], "Wh");
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialize_data() {
+    let (_, data) = Data::parse(&[0x02, 0x04, 0x03, 0x01, 0x0d, 0x42, 0x0d, 0x4a, 0x04, 0x0a, 0xa0, 0x40]).unwrap();
+
+    assert_eq!(
+      serde_json::to_value(data).unwrap(),
+      serde_json::json!([true, 42, [0x4a], { "bits": "1010000001", "hex": "a040" }])
+    );
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialize_security_control() {
+    let (_, apdu) = Apdu::parse(&ENCRYPTED_MESSAGE).unwrap();
+
+    assert_eq!(
+      serde_json::to_value(apdu).unwrap()["GeneralGloCiphering"]["security_control"],
+      serde_json::json!({
+        "suite_id": 1,
+        "authentication": false,
+        "encryption": true,
+        "broadcast": false,
+        "compression": false,
+      })
+    );
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialize_apdu() {
+    let bytes = [0x0f, 0x00, 0x00, 0x55, 0x39, 0x00, 0x02, 0x01, 0x06, 0x00, 0x00, 0x04, 0xd2];
+    let (_, apdu) = Apdu::parse(&bytes).unwrap();
+
+    assert_eq!(
+      serde_json::to_value(apdu).unwrap(),
+      serde_json::json!({
+        "DataNotification": {
+          "long_invoke_id_and_priority": {
+            "invoke_id": 21817,
+            "priority": "Normal",
+            "service_class": "Unconfirmed",
+            "processing_option": "ContinueOnError",
+            "self_descriptive": false,
+          },
+          "date_time": null,
+          "notification_body": [1234],
+        },
+      })
+    );
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serialize_long_invoke_id_and_priority() {
+    let long_invoke_id_and_priority = LongInvokeIdAndPriority(0xd0000042);
+
+    assert_eq!(
+      serde_json::to_value(long_invoke_id_and_priority).unwrap(),
+      serde_json::json!({
+        "invoke_id": 0x42,
+        "priority": "High",
+        "service_class": "Confirmed",
+        "processing_option": "ContinueOnError",
+        "self_descriptive": true,
+      })
+    );
+  }
+
+  #[test]
+  fn parse_get_request_and_response() {
+    #[rustfmt::skip]
+    let request: [u8; 19] = [
+      0xC0, // Type (Get Request)
+        0x01, // Get Request Normal
+        0xC1, // Invoke ID & Priority
+        // Attribute Descriptor
+          0x00, 0x07, // Class ID (Profile Generic)
+          0x01, 0x00, 0x63, 0x01, 0x00, 0xFF, // Instance ID
+          0x02, // Attribute ID
+        0x01, // Access Selection (Present)
+          0x02, // Access Selector (Entry Descriptor)
+          0x02, 0x01, // Type (Structure), Length
+            0x12, 0x00, 0x01, // Type (Long Unsigned)
+    ];
+
+    let (_, apdu) = Apdu::parse(&request).unwrap();
+    let get_request = match apdu {
+      Apdu::GetRequest(get_request) => get_request,
+      _ => unreachable!(),
+    };
+    assert_eq!(get_request.attribute_descriptor().class_id(), 7);
+    let access_selection = get_request.access_selection().unwrap();
+    assert_eq!(access_selection.access_selector(), 2);
+    assert_eq!(access_selection.access_parameters(), &Data::Structure(vec![Data::LongUnsigned(1)]));
+
+    let response: [u8; 5] = [0xC4, 0x01, 0xC1, 0x01, 0x04];
+    let (_, apdu) = Apdu::parse(&response).unwrap();
+    let get_response = match apdu {
+      Apdu::GetResponse(get_response) => get_response,
+      _ => unreachable!(),
+    };
+    assert_eq!(get_response.result(), Err(DataAccessResult::ObjectUndefined));
+  }
+
+  #[test]
+  fn parse_set_request_and_response() {
+    #[rustfmt::skip]
+    let request: [u8; 16] = [
+      0xC1, // Type (Set Request)
+        0x01, // Set Request Normal
+        0xC1, // Invoke ID & Priority
+        // Attribute Descriptor
+          0x00, 0x08, // Class ID (Clock)
+          0x00, 0x00, 0x01, 0x00, 0x00, 0xFF, // Instance ID
+          0x03, // Attribute ID
+        0x00, // Access Selection (Absent)
+        0x10, // Type (Long)
+          0x00, 0x3C, // Long
+    ];
+
+    let (_, apdu) = Apdu::parse(&request).unwrap();
+    let set_request = match apdu {
+      Apdu::SetRequest(set_request) => set_request,
+      _ => unreachable!(),
+    };
+    assert_eq!(set_request.invoke_id_and_priority().invoke_id(), 1);
+    assert_eq!(set_request.attribute_descriptor().class_id(), 8);
+    assert_eq!(set_request.attribute_descriptor().instance_id(), &ObisCode::new(0, 0, 1, 0, 0, 255));
+    assert_eq!(set_request.attribute_descriptor().attribute_id(), 3);
+    assert_eq!(set_request.access_selection(), None);
+    assert_eq!(set_request.value(), &Data::Long(60));
+
+    let response: [u8; 4] = [0xC5, 0x01, 0xC1, 0x03];
+    let (_, apdu) = Apdu::parse(&response).unwrap();
+    let set_response = match apdu {
+      Apdu::SetResponse(set_response) => set_response,
+      _ => unreachable!(),
+    };
+    assert_eq!(set_response.result(), DataAccessResult::ReadWriteDenied);
+  }
+
+  #[test]
+  fn parse_action_request_and_response() {
+    #[rustfmt::skip]
+    let request: [u8; 15] = [
+      0xC3, // Type (Action Request)
+        0x01, // Action Request Normal
+        0xC1, // Invoke ID & Priority
+        // Method Descriptor
+          0x00, 0x46, // Class ID (Disconnect Control)
+          0x00, 0x00, 0x60, 0x03, 0x0A, 0xFF, // Instance ID
+          0x01, // Method ID (Remote Disconnect)
+        0x01, // Method Invocation Parameters (Present)
+          0x0F, // Type (Integer)
+            0x00, // Integer
+    ];
+
+    let (_, apdu) = Apdu::parse(&request).unwrap();
+    let action_request = match apdu {
+      Apdu::ActionRequest(action_request) => action_request,
+      _ => unreachable!(),
+    };
+    assert_eq!(action_request.method_descriptor().class_id(), 70);
+    assert_eq!(action_request.method_descriptor().method_id(), 1);
+    assert_eq!(action_request.method_invocation_parameters(), Some(&Data::Integer(0)));
+
+    let response: [u8; 5] = [0xC7, 0x01, 0xC1, 0x00, 0x00];
+    let (_, apdu) = Apdu::parse(&response).unwrap();
+    let action_response = match apdu {
+      Apdu::ActionResponse(action_response) => action_response,
+      _ => unreachable!(),
+    };
+    assert_eq!(action_response.result(), ActionResult::Success);
+    assert_eq!(action_response.return_parameters(), None);
+  }
+
+  #[test]
+  fn parse_event_notification() {
+    #[rustfmt::skip]
+    let payload: [u8; 26] = [
+      0xC2, // Type (Event Notification)
+        0x01, // Time (Present)
+          0x0C, // Length
+            0x07, 0xE5, 0x09, 0x0B, 0x06, 0x09, 0x0D, 0x14, 0x00, 0xFF, 0x88, 0x80,
+        // Attribute Descriptor
+          0x00, 0x07, // Class ID (Profile Generic)
+          0x00, 0x00, 0x63, 0x62, 0x00, 0xFF, // Instance ID
+          0x02, // Attribute ID
+        0x16, // Type (Enum)
+          0x2A, // Enum
+    ];
+
+    let (_, apdu) = Apdu::parse(&payload).unwrap();
+    let event_notification = match apdu {
+      Apdu::EventNotification(event_notification) => event_notification,
+      _ => unreachable!(),
+    };
+    assert_eq!(event_notification.time().map(|time| time.to_string()), Some("2021-09-11T09:13:20.00+02:00".into()));
+    assert_eq!(event_notification.attribute_descriptor().instance_id(), &ObisCode::new(0, 0, 99, 98, 0, 255));
+    assert_eq!(event_notification.attribute_value(), &Data::Enum(42));
+  }
+
+  #[test]
+  fn parse_read_request_and_response() {
+    let request: [u8; 5] = [0x05, 0x01, 0x02, 0xFA, 0x00];
+    let (_, apdu) = Apdu::parse(&request).unwrap();
+    assert_eq!(
+      apdu,
+      Apdu::ReadRequest(ReadRequest {
+        variable_access_specifications: vec![VariableAccessSpecification::VariableName(0xFA00)],
+      }),
+    );
+
+    let response: [u8; 8] = [0x0C, 0x02, 0x00, 0x12, 0x00, 0xE6, 0x01, 0x04];
+    let (_, apdu) = Apdu::parse(&response).unwrap();
+    assert_eq!(
+      apdu,
+      Apdu::ReadResponse(ReadResponse {
+        results: vec![
+          ReadResult::Data(Data::LongUnsigned(230)),
+          ReadResult::DataAccessError(DataAccessResult::ObjectUndefined),
+        ],
+      }),
+    );
+  }
+
+  const KEY: [u8; 16] = 0xdeafbeefcafebabedeafbeefcafebabeu128.to_be_bytes();
+
+  #[rustfmt::skip]
+  const ENCRYPTED_MESSAGE: [u8; 354] = [
+    0xdb, // Tag
+    0x08, 0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9, // System Title
+    0x82, 0x01, 0x55, // Payload Length
+    0x21, // Security Control
+    0x00, 0x02, 0xbc, 0x66, // Invocation Counter
+    0xf4, 0x50, 0xb5, 0x97, 0xb1, 0x1f, 0x09, 0x45, 0x0a, 0x68, 0x03, 0x63, 0xe7, 0x18, 0x41, 0xc4,
+    0x09, 0x82, 0x9a, 0xab, 0xe0, 0x8b, 0x44, 0x3f, 0x6c, 0x9a, 0x70, 0x73, 0xbc, 0xc4, 0x5c, 0xdb,
+    0x8b, 0x57, 0x48, 0x85, 0x11, 0x80, 0x42, 0x0c, 0x79, 0xd9, 0x0e, 0x26, 0xf1, 0x26, 0x15, 0xbe,
+    0xed, 0x5f, 0xea, 0x7d, 0xc8, 0x54, 0x26, 0xaf, 0x38, 0x9c, 0x8c, 0x92, 0x02, 0x9f, 0xf3, 0x64,
+    0x63, 0xf7, 0xbf, 0x1b, 0x9e, 0x56, 0xa3, 0x88, 0x75, 0x69, 0xf6, 0x1a, 0x5a, 0x86, 0x23, 0x9a,
+    0xd6, 0x2f, 0xda, 0x85, 0x48, 0xb3, 0xf6, 0x22, 0x61, 0x25, 0x3f, 0xe5, 0xcd, 0x0e, 0x06, 0xb7,
+    0x14, 0xad, 0x5c, 0x26, 0x85, 0xc8, 0x45, 0x57, 0x70, 0x8d, 0x57, 0xde, 0xba, 0x10, 0xca, 0xc0,
+    0x8d, 0xeb, 0xba, 0xcc, 0xc5, 0x66, 0x2b, 0x45, 0x50, 0x14, 0xbc, 0x8b, 0x44, 0x17, 0x48, 0x1d,
+    0x2b, 0x9a, 0xf1, 0x66, 0x22, 0x07, 0x1f, 0xbe, 0xef, 0x5e, 0xce, 0xaf, 0x1e, 0x39, 0xf7, 0x99,
+    0x6c, 0xa9, 0x98, 0x27, 0x68, 0x31, 0xe6, 0x84, 0xe0, 0x70, 0x44, 0x57, 0xd4, 0xcd, 0x64, 0x96,
+    0xca, 0xd4, 0xdb, 0xd9, 0x03, 0x35, 0x98, 0x11, 0x13, 0x5e, 0x7e, 0x70, 0xb4, 0x06, 0x30, 0x4c,
+    0x8e, 0x7e, 0xce, 0x20, 0x90, 0xcd, 0x74, 0x3a, 0x08, 0x2d, 0xa6, 0x2e, 0xd6, 0x20, 0x83, 0xb3,
+    0xd3, 0xf1, 0x21, 0xf9, 0x97, 0x2d, 0xd6, 0x48, 0x78, 0x86, 0xf6, 0xaf, 0x2c, 0x5c, 0x76, 0x39,
+    0x81, 0xa2, 0xe1, 0xa1, 0x28, 0x3c, 0x52, 0x12, 0xa8, 0x15, 0x77, 0x84, 0x7d, 0x40, 0xf7, 0x64,
+    0xba, 0x93, 0x6d, 0x26, 0xc6, 0x33, 0xec, 0x73, 0xb0, 0x1b, 0xc7, 0x1a, 0xfd, 0x6d, 0x4c, 0x10,
+    0xbb, 0xcb, 0xea, 0x96, 0x86, 0xf0, 0x3d, 0x40, 0x84, 0x99, 0xee, 0x7f, 0x16, 0x35, 0x69, 0xea,
+    0x7d, 0xb6, 0xf5, 0x23, 0xea, 0xbd, 0xfe, 0x5d, 0x31, 0xb5, 0xb2, 0x34, 0xf3, 0x09, 0xc5, 0x71,
+    0xbc, 0xec, 0x4f, 0x3f, 0xae, 0x4c, 0xe9, 0xab, 0xce, 0x92, 0x62, 0x4a, 0x37, 0xeb, 0x62, 0x0d,
+    0x2c, 0x2a, 0xdd, 0xf6, 0x0c, 0xd5, 0xaa, 0x65, 0xd1, 0xe2, 0xe4, 0x5c, 0xe2, 0x13, 0x4f, 0x0e,
+    0x4c, 0x2f, 0x70, 0xe1, 0x9d, 0x93, 0x6f, 0x84, 0x5c, 0x6f, 0x36, 0x91, 0xb3, 0x26, 0x00, 0x5d,
+    0x43, 0x9c, 0xe6, 0x46, 0x27, 0x53, 0x92, 0xf6, 0x0b, 0x3b, 0x69, 0x90, 0x3f, 0x82, 0x84, 0x78,
+  ];
+
+  #[rustfmt::skip]
+  const DECRYPTED_MESSAGE: [u8; 336] = [
+    0x0f, 0x00, 0x02, 0xb5, 0xe4, 0x0c, 0x07, 0xe5, 0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0x00, 0xff,
+    0x88, 0x80, 0x02, 0x10, 0x09, 0x06, 0x00, 0x00, 0x01, 0x00, 0x00, 0xff, 0x09, 0x0c, 0x07, 0xe5,
+    0x09, 0x0b, 0x06, 0x09, 0x0d, 0x14, 0x00, 0xff, 0x88, 0x80, 0x02, 0x02, 0x09, 0x06, 0x00, 0x00,
+    0x60, 0x01, 0x00, 0xff, 0x09, 0x0e, 0x31, 0x4b, 0x46, 0x4d, 0x30, 0x32, 0x30, 0x30, 0x30, 0x37,
+    0x30, 0x33, 0x31, 0x33, 0x02, 0x02, 0x09, 0x06, 0x00, 0x00, 0x2a, 0x00, 0x00, 0xff, 0x09, 0x10,
+    0x4b, 0x46, 0x4d, 0x31, 0x32, 0x30, 0x30, 0x32, 0x30, 0x30, 0x30, 0x37, 0x30, 0x33, 0x31, 0x33,
+    0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x20, 0x07, 0x00, 0xff, 0x12, 0x09, 0x20, 0x02, 0x02, 0x0f,
+    0xff, 0x16, 0x23, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x34, 0x07, 0x00, 0xff, 0x12, 0x09, 0x04,
+    0x02, 0x02, 0x0f, 0xff, 0x16, 0x23, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x48, 0x07, 0x00, 0xff,
+    0x12, 0x09, 0x0d, 0x02, 0x02, 0x0f, 0xff, 0x16, 0x23, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x1f,
+    0x07, 0x00, 0xff, 0x12, 0x01, 0x6d, 0x02, 0x02, 0x0f, 0xfe, 0x16, 0x21, 0x02, 0x03, 0x09, 0x06,
+    0x01, 0x00, 0x33, 0x07, 0x00, 0xff, 0x12, 0x03, 0x0e, 0x02, 0x02, 0x0f, 0xfe, 0x16, 0x21, 0x02,
+    0x03, 0x09, 0x06, 0x01, 0x00, 0x47, 0x07, 0x00, 0xff, 0x12, 0x02, 0x62, 0x02, 0x02, 0x0f, 0xfe,
+    0x16, 0x21, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x01, 0x07, 0x00, 0xff, 0x06, 0x00, 0x00, 0x0f,
+    0x5e, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x1b, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x02, 0x07, 0x00,
+    0xff, 0x06, 0x00, 0x00, 0x00, 0x00, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x1b, 0x02, 0x03, 0x09, 0x06,
+    0x01, 0x00, 0x01, 0x08, 0x00, 0xff, 0x06, 0x00, 0x51, 0x00, 0x15, 0x02, 0x02, 0x0f, 0x00, 0x16,
+    0x1e, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x02, 0x08, 0x00, 0xff, 0x06, 0x00, 0x00, 0x00, 0x00,
+    0x02, 0x02, 0x0f, 0x00, 0x16, 0x1e, 0x02, 0x03, 0x09, 0x06, 0x01, 0x00, 0x03, 0x08, 0x00, 0xff,
+    0x06, 0x00, 0x00, 0x34, 0x65, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x20, 0x02, 0x03, 0x09, 0x06, 0x01,
+    0x00, 0x04, 0x08, 0x00, 0xff, 0x06, 0x00, 0x08, 0xa3, 0xbc, 0x02, 0x02, 0x0f, 0x00, 0x16, 0x20,
+  ];
+
+  #[cfg(feature = "hdlcparse")]
+  #[test]
+  fn hdlc_llc_header() {
+    use hdlc::{Destination, MessageType};
+    use hdlcparse::type3::HdlcFrame;
+
+    let bytes = fixtures::hdlc_frame(&[0xe6, 0xe7, 0x01, 0xc5, 0x01, 0xc1, 0x00]);
+    let (_, frame) = HdlcFrame::parse(&bytes).unwrap();
+    let frames = [frame];
+
+    assert!(<HdlcDataLinkLayer>::next_frame_with_header(&frames[..]).is_err());
+
+    let (rest, information, header) = HdlcDataLinkLayer::<false>::next_frame_with_header(&frames[..]).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(information, &[0xc5, 0x01, 0xc1, 0x00][..]);
+    let header = header.unwrap();
+    assert_eq!(header.destination(), Destination::Unicast);
+    assert_eq!(header.message_type(), MessageType::Response);
+    assert_eq!(header.quality(), 0x01);
+
+    let bytes = fixtures::hdlc_frame(&[0xc5, 0x01, 0xc1, 0x00]);
+    let (_, frame) = HdlcFrame::parse(&bytes).unwrap();
+    let (_, information, header) = <HdlcDataLinkLayer>::next_frame_with_header(&[frame][..]).unwrap();
+    assert_eq!(information, &[0xc5, 0x01, 0xc1, 0x00][..]);
+    assert_eq!(header, None);
+  }
+
+  #[cfg(feature = "hdlcparse")]
+  #[test]
+  fn hdlc_segmented_frames() {
+    use hdlcparse::type3::HdlcFrame;
+
+    let bytes = [
+      fixtures::hdlc_segment(&[0xc5, 0x01], true),
+      fixtures::hdlc_segment(&[0xc1], true),
+      fixtures::hdlc_segment(&[0x00], false),
+      fixtures::hdlc_frame(&[0xc5, 0x02, 0xc1, 0x00]),
+    ];
+    let frames = bytes.iter().map(|bytes| HdlcFrame::parse(bytes).unwrap().1).collect::<Vec<_>>();
+
+    let (rest, information) = <HdlcDataLinkLayer>::next_frame(&frames[..]).unwrap();
+    assert_eq!(information, &[0xc5, 0x01, 0xc1, 0x00][..]);
+    assert_eq!(rest.len(), 1);
+
+    let (rest, information) = <HdlcDataLinkLayer>::next_frame(rest).unwrap();
+    assert_eq!(information, &[0xc5, 0x02, 0xc1, 0x00][..]);
+    assert!(rest.is_empty());
+
+    assert!(matches!(<HdlcDataLinkLayer>::next_frame(&frames[..2]), Err(Error::Incomplete(_))));
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn test_decoder() {
+    let bytes = fixtures::segmented_mbus_message(&DECRYPTED_MESSAGE);
+
+    let mut decoder = DlmsDecoder::<MBusDataLinkLayer>::new(Dlms::new(KEY));
+
+    let mut results = Vec::new();
+    for chunk in bytes.chunks(7) {
+      results.extend(decoder.push(chunk));
+    }
+
+    assert_eq!(results.len(), 1);
+    let obis = results.remove(0).unwrap();
+    assert_eq!(obis.get(&codes::ACTIVE_ENERGY_IMPORT).map(|reg| reg.value()), Some(&Data::Float64(5308437.0)));
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn test_decoder_control_frames() {
+    const ACK: [u8; 1] = [0xe5];
+    const SHORT_FRAME: [u8; 5] = [0x10, 0x40, 0xfe, 0x3e, 0x16];
+
+    let mut decoder = DlmsDecoder::<MBusDataLinkLayer>::new(Dlms::new(KEY));
+
+    // Control frames on their own are dropped instead of waiting for a message.
+    assert!(decoder.push(&ACK).is_empty());
+    assert!(decoder.push(&SHORT_FRAME).is_empty());
+    assert!(!decoder.has_pending_input());
+
+    // Control frames interleaved between segments are skipped.
+    let mut bytes = fixtures::segmented_mbus_message(&DECRYPTED_MESSAGE);
+    let first_segment_len = bytes[1] as usize + 6;
+    bytes.splice(first_segment_len..first_segment_len, ACK.iter().chain(&SHORT_FRAME).copied());
+
+    let mut results = Vec::new();
+    for chunk in bytes.chunks(7) {
+      results.extend(decoder.push(chunk));
+    }
+
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_ok());
+    assert!(!decoder.has_pending_input());
+  }
+
+  #[cfg(all(feature = "std", feature = "mbusparse"))]
+  #[test]
+  fn test_reader() {
+    let mut bytes = vec![0x00];
+    bytes.extend(fixtures::segmented_mbus_message(&DECRYPTED_MESSAGE));
+
+    let reader = DlmsReader::<_, MBusDataLinkLayer>::new(std::io::Cursor::new(bytes), Dlms::new(KEY));
+    let results = reader.collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 2);
+    assert!(matches!(results[0], Err(Error::InvalidFormat)));
+    assert!(results[1].is_ok());
+  }
+
+  #[cfg(all(feature = "std", feature = "mbusparse"))]
+  #[test]
+  fn test_reader_io_error() {
+    struct Failing;
+
+    impl std::io::Read for Failing {
+      fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+      }
+    }
+
+    let mut reader = DlmsReader::<_, MBusDataLinkLayer>::new(Failing, Dlms::new(KEY));
+    assert!(matches!(reader.next(), Some(Err(Error::Io))));
+    assert_eq!(reader.io_error().map(|err| err.kind()), Some(std::io::ErrorKind::BrokenPipe));
+    assert!(reader.next().is_none());
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn test_parse_mbus_plaintext() {
+    use mbusparse::Telegram;
+
+    fn parse_telegrams(bytes: &[u8]) -> Vec<Telegram<'_>> {
+      let mut input = bytes;
+      let mut telegrams = Vec::new();
+      while !input.is_empty() {
+        let (rest, telegram) = Telegram::parse(input).unwrap();
+        telegrams.push(telegram);
+        input = rest;
+      }
+      telegrams
+    }
+
+    let bytes = fixtures::segmented_mbus_message(&DECRYPTED_MESSAGE);
+    let telegrams = parse_telegrams(&bytes);
+    let (rest, apdu) = mbus::parse_mbus_plaintext(&telegrams).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(apdu, Apdu::parse(&DECRYPTED_MESSAGE).unwrap().1);
+
+    let bytes = fixtures::segmented_mbus_message(&ENCRYPTED_MESSAGE);
+    assert!(matches!(Apdu::try_from(&parse_telegrams(&bytes)[..]), Err(Error::KeyRequired)));
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn test_mbus_header() {
+    use mbusparse::Telegram;
+
+    let mut user_data = vec![0x78, 0x56, 0x34, 0x12, 0x2d, 0x2c, 0x01, 0x02, 0x2a, 0x00, 0x10, 0x05];
+    user_data.extend(&[0xc5, 0x01, 0xc1, 0x00]);
+    let bytes = fixtures::mbus_long_frame(0x7c, &user_data);
+    let (_, telegram) = Telegram::parse(&bytes).unwrap();
+    let telegrams = [telegram.clone(), telegram];
+
+    let (rest, frame, header) = MBusDataLinkLayer::next_frame_with_header(&telegrams[..]).unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(frame, &[0xc5, 0x01, 0xc1, 0x00][..]);
+
+    let header = header.unwrap();
+    let address = header.address().unwrap();
+    assert_eq!(address.identification_number(), 12345678);
+    assert_eq!(address.manufacturer(), 0x2c2d);
+    assert_eq!(address.version(), 0x01);
+    assert_eq!(address.device_type(), 0x02);
+    assert_eq!(header.access_number(), 0x2a);
+    assert_eq!(header.status(), 0x00);
+    assert_eq!(header.configuration(), 0x0510);
+
+    let (rest, _, _) = MBusDataLinkLayer::next_frame_for_device(&telegrams, 12345678).unwrap();
+    assert_eq!(rest.len(), 1);
+    assert!(matches!(MBusDataLinkLayer::next_frame_for_device(&telegrams, 87654321), Err(Error::Incomplete(_))));
+
+    assert_eq!(address.system_title(), [0x2d, 0x2c, 0x78, 0x56, 0x34, 0x12, 0x01, 0x02]);
+    assert_eq!(MBusDataLinkLayer::system_title(&Some(header.clone())), Some(address.system_title()));
+
+    // Generic M-Bus long data header.
+    let bytes = fixtures::mbus_long_frame(0x72, &user_data);
+    let (_, telegram) = Telegram::parse(&bytes).unwrap();
+    let (_, frame, long_header) = MBusDataLinkLayer::next_frame_with_header(&[telegram][..]).unwrap();
+    assert_eq!(frame, &[0xc5, 0x01, 0xc1, 0x00][..]);
+    assert_eq!(long_header, Some(header));
+
+    // Generic M-Bus short data header.
+    let bytes = fixtures::mbus_long_frame(0x7a, &user_data[8..]);
+    let (_, telegram) = Telegram::parse(&bytes).unwrap();
+    let (_, frame, short_header) = MBusDataLinkLayer::next_frame_with_header(&[telegram][..]).unwrap();
+    assert_eq!(frame, &[0xc5, 0x01, 0xc1, 0x00][..]);
+    assert_eq!(short_header.unwrap().address(), None);
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn decrypt_unsegmented_mbus_frames() {
+    use mbusparse::Telegram;
+
+    // The long header contains a 12 byte address and the short header 4 bytes,
+    // each ending with the configuration word as a little-endian `u16`.
+    let long_header = [0x78, 0x56, 0x34, 0x12, 0x2d, 0x2c, 0x01, 0x02, 0x2a, 0x00, 0x00, 0x00];
+    let short_header = [0x2b, 0x00, 0x00, 0x00];
+    let bytes = [
+      fixtures::mbus_long_frame(0x72, &[&long_header[..], &fixtures::energy_notification(1)].concat()),
+      fixtures::mbus_long_frame(0x7a, &[&short_header[..], &fixtures::energy_notification(2)].concat()),
+    ];
+    let telegrams = bytes.iter().map(|bytes| Telegram::parse(bytes).unwrap().1).collect::<Vec<_>>();
+
+    let dlms = Dlms::new(KEY);
+    let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+
+    let (rest, obis_map) = dlms.decrypt::<MBusDataLinkLayer, _>(&telegrams[..]).unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(obis_map[&energy].value(), &Data::DoubleLongUnsigned(1));
+
+    let (rest, obis_map) = dlms.decrypt::<MBusDataLinkLayer, _>(rest).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(obis_map[&energy].value(), &Data::DoubleLongUnsigned(2));
+  }
+
+  #[test]
+  fn decrypt_with_short_invocation_counter() {
+    let system_title = [0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9];
+    let apdu = fixtures::energy_notification(1234);
+
+    // Drop the leading zero bytes of the invocation counter.
+    let mut ciphered = fixtures::glo_ciphering(&KEY, system_title, 0x0102, &apdu).unwrap();
+    ciphered.drain(12..14);
+    ciphered[10] -= 2;
+
+    assert!(Dlms::new(KEY).decrypt_frame(&ciphered, &mut Vec::new()).is_err());
+
+    assert!(Dlms::new(KEY).with_invocation_counter_width(0).is_err());
+    assert!(Dlms::new(KEY).with_invocation_counter_width(5).is_err());
+
+    let dlms = Dlms::new(KEY).with_invocation_counter_width(2).unwrap();
+    let (apdu, meta) = dlms.decrypt_frame(&ciphered, &mut Vec::new()).unwrap();
+    assert_eq!(meta.invocation_counter(), Some(0x0102));
+    let (_, obis_map) = ObisMap::parse(&apdu).unwrap();
+    assert_eq!(obis_map[&ObisCode::new(1, 0, 1, 8, 0, 255)].value(), &Data::DoubleLongUnsigned(1234));
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn decrypt_with_header_system_title() {
+    use mbusparse::Telegram;
+
+    let system_title = [0x2d, 0x2c, 0x78, 0x56, 0x34, 0x12, 0x01, 0x02];
+    let apdu = fixtures::energy_notification(1234);
+
+    // The APDU contains a different system title than the one used for encryption.
+    let mut ciphered = fixtures::glo_ciphering(&KEY, system_title, 1, &apdu).unwrap();
+    ciphered[2..10].fill(0);
+
+    let mut user_data = vec![0x78, 0x56, 0x34, 0x12, 0x2d, 0x2c, 0x01, 0x02, 0x2a, 0x00, 0x10, 0x05];
+    user_data.extend(ciphered);
+    let bytes = fixtures::mbus_long_frame(0x7c, &user_data);
+    let (_, telegram) = Telegram::parse(&bytes).unwrap();
+    let telegrams = [telegram];
+
+    let dlms = Dlms::new(KEY);
+    assert!(dlms.decrypt::<MBusDataLinkLayer, _>(&telegrams[..]).is_err());
+    let (_, obis_map) = dlms.decrypt_with_header_system_title::<MBusDataLinkLayer, _>(&telegrams[..]).unwrap();
+    assert_eq!(obis_map[&ObisCode::new(1, 0, 1, 8, 0, 255)].value(), &Data::DoubleLongUnsigned(1234));
+  }
+
+  #[test]
+  fn parse_concatenated_apdus() {
+    let notifications = (1..=3).map(fixtures::energy_notification).collect::<Vec<_>>().concat();
+    let ciphered = fixtures::glo_ciphering(&KEY, [0; 8], 1, &notifications).unwrap();
+
+    let ciphering = match Apdu::parse(&ciphered) {
+      Ok((_, Apdu::GeneralGloCiphering(ciphering))) => ciphering,
+      apdu => panic!("unexpected APDU: {:?}", apdu),
+    };
+    let payload = ciphering.decrypt((&KEY).into()).unwrap();
+    let (_, apdus) = Apdu::parse_all(&payload).unwrap();
+
+    let values = apdus
+      .iter()
+      .map(|apdu| ObisMap::parse(apdu).unwrap().1[&ObisCode::new(1, 0, 1, 8, 0, 255)].value().clone())
+      .collect::<Vec<_>>();
+    assert_eq!(values, [Data::DoubleLongUnsigned(1), Data::DoubleLongUnsigned(2), Data::DoubleLongUnsigned(3)]);
+
+    assert!(Apdu::parse_all(&payload[..payload.len() - 1]).is_err());
+    assert!(Apdu::parse_all(&[]).is_err());
+  }
+
+  #[test]
+  fn dump_frames() {
+    let dump = debug::dump(&DECRYPTED_MESSAGE);
+    assert!(dump.starts_with("0000  0f DataNotification\n"));
+    assert!(!dump.contains("error"));
+
+    let mut message = DECRYPTED_MESSAGE.to_vec();
+    message[20] = 0xff;
+    assert!(debug::dump(&message).lines().last().unwrap().contains("error: invalid format"));
+    assert!(debug::dump(&message).ends_with("0014      error: invalid format (Fail)\n"));
+
+    assert_eq!(
+      debug::dump(&ENCRYPTED_MESSAGE[..]),
+      "0000  db GeneralGloCiphering(system_title=KFM10200112a9, encrypted, counter=179302)\n"
+    );
+    assert_eq!(debug::dump(&[0xff]), "0000  error: invalid format (Fail)\n");
+    assert_eq!(debug::dump_data(&[0x11, 0x01, 0x00]), "0000  11 Unsigned(1)\n0002  1 trailing bytes\n");
+  }
+
+  #[test]
+  fn parse_malformed_input() {
+    fn parse_all(bytes: &[u8]) {
+      let _ = Apdu::parse(bytes);
+      let _ = Apdu::parse_encrypted(bytes, (&KEY).into());
+      let _ = parse(bytes, Some((&KEY).into()));
+      let _ = GeneralGloCiphering::parse(bytes);
+      let _ = Data::parse(bytes);
+      let _ = DataRef::parse(bytes);
+    }
+
+    let mut corpus = Vec::new();
+
+    for message in [&ENCRYPTED_MESSAGE[..], &DECRYPTED_MESSAGE[..]] {
+      corpus.extend((0..message.len()).map(|len| message[..len].to_vec()));
+      for i in 0..message.len() {
+        let mut message = message.to_vec();
+        message[i] ^= 0xff;
+        corpus.push(message);
+      }
+    }
+
+    for tag in 0..=u8::MAX {
+      corpus.push(vec![tag]);
+      corpus.push(vec![tag, 0x01, 0x00, 0x00, 0x00, 0x00]);
+      corpus.push(vec![tag, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    // Deeply nested structures must not overflow the stack.
+    corpus.push([0x02, 0x01].repeat(100_000));
+    corpus.push([0x01, 0x01].repeat(100_000));
+    corpus.push([&[0x13, 0x01][..], &[0x01, 0x7f].repeat(100_000)].concat());
+
+    for bytes in corpus {
+      parse_all(&bytes);
+    }
+
+    let nested = [[0x02, 0x01].repeat(100), vec![0x00]].concat();
+    assert!(Data::parse(&nested).is_err());
+  }
+
+  #[test]
+  fn general_glo_ciphering_initialization_vector() {
+    let (_, apdu) = Apdu::parse(&ENCRYPTED_MESSAGE).unwrap();
+    let mut ciphering = match apdu {
+      Apdu::GeneralGloCiphering(ciphering) => ciphering,
+      apdu => panic!("unexpected APDU: {:?}", apdu),
+    };
+
+    assert!(ciphering.is_encrypted());
+    assert_eq!(
+      Apdu::GeneralGloCiphering(ciphering.clone()).to_string(),
+      "GeneralGloCiphering(system_title=KFM10200112a9, encrypted, counter=179302)"
+    );
+    assert_eq!(
+      ciphering.initialization_vector(),
+      Some([0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9, 0x00, 0x02, 0xbc, 0x66]),
+    );
+
+    ciphering.set_invocation_counter(Some(0x01020304));
+    assert_eq!(
+      ciphering.initialization_vector(),
+      Some([0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9, 0x01, 0x02, 0x03, 0x04]),
+    );
+  }
+
+  #[test]
+  fn decrypt_with_invocation_counter() {
+    let dlms = Dlms::new(KEY);
+    let (_, expected) = dlms.decrypt::<RawDataLinkLayer, _>(&ENCRYPTED_MESSAGE[..]).unwrap();
+
+    // A meter which transmits a different counter than the one used for encryption.
+    let mut message = ENCRYPTED_MESSAGE;
+    message[14..18].copy_from_slice(&[0x00, 0x00, 0x00, 0x01]);
+    assert!(dlms.decrypt::<RawDataLinkLayer, _>(&message[..]).is_err());
+
+    let (rest, obis) = dlms.decrypt_with_invocation_counter::<RawDataLinkLayer, _>(&message[..], 0x0002bc66).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(obis, expected);
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn decrypt_into_scratch() {
+    use mbusparse::Telegram;
+
+    let bytes = fixtures::segmented_mbus_message(&ENCRYPTED_MESSAGE);
+    let (rest, first) = Telegram::parse(&bytes).unwrap();
+    let (_, second) = Telegram::parse(rest).unwrap();
+    let telegrams = [first, second];
+
+    let dlms = Dlms::new(KEY);
+    let mut scratch = DecodeScratch::new();
+    for _ in 0..2 {
+      let (rest, obis) = dlms.decrypt_into::<MBusDataLinkLayer, _>(&telegrams[..], &mut scratch).unwrap();
+      assert!(rest.is_empty());
+      assert_eq!(obis, dlms.decrypt::<MBusDataLinkLayer, _>(&telegrams[..]).unwrap().1);
+    }
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn segment_count() {
+    use mbusparse::Telegram;
+
+    let bytes = fixtures::segmented_mbus_message(&ENCRYPTED_MESSAGE);
+    let (rest, first) = Telegram::parse(&bytes).unwrap();
+    let (_, second) = Telegram::parse(rest).unwrap();
+    let telegrams = [first, second];
+
+    let (_, _, meta) = Dlms::new(KEY).decrypt_with_meta::<MBusDataLinkLayer, _>(&telegrams[..]).unwrap();
+    assert_eq!(meta.segment_count(), 2);
+    assert!(meta.segmented());
+
+    let (consumed, _) = Dlms::new(KEY).decrypt_one::<MBusDataLinkLayer, _>(&telegrams[..]).unwrap();
+    assert_eq!(consumed, 2);
+
+    let (_, _, meta) = Dlms::new(KEY).decrypt_with_meta::<RawDataLinkLayer, _>(&ENCRYPTED_MESSAGE[..]).unwrap();
+    assert_eq!(meta.segment_count(), 1);
+    assert!(!meta.segmented());
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn strip_block_crcs() {
+    use mbus::block_crc;
+
+    assert_eq!(block_crc(b"123456789"), 0xc2b7);
+
+    // L-field followed by 29 bytes, split into blocks of 10, 16 and 4 bytes.
+    let mut data = vec![29];
+    data.extend(1..30);
+
+    let mut frame = Vec::new();
+    for block in [&data[..10], &data[10..26], &data[26..]] {
+      frame.extend(block);
+      frame.extend(block_crc(block).to_be_bytes());
+    }
+
+    assert_eq!(mbus::strip_block_crcs(&frame).unwrap(), data);
+    assert!(matches!(mbus::strip_block_crcs(&frame[..frame.len() - 1]), Err(Error::Incomplete(_))));
+    assert_eq!(mbus::strip_block_crcs(&frame[..frame.len() - 3]), Err(Error::Incomplete(NonZeroUsize::new(3))));
+    assert_eq!(mbus::strip_block_crcs(&[]), Err(Error::Incomplete(NonZeroUsize::new(1))));
+
+    frame[20] ^= 0x01;
+    assert_eq!(mbus::strip_block_crcs(&frame), Err(Error::ChecksumMismatch));
+  }
+
+  #[cfg(feature = "mbusparse")]
+  #[test]
+  fn three_segments() {
+    use mbusparse::Telegram;
+
+    let bytes = fixtures::segmented_mbus_message_with_len(&ENCRYPTED_MESSAGE, 120);
+    let mut telegrams = Vec::new();
+    let mut input = &bytes[..];
+    while !input.is_empty() {
+      let (rest, telegram) = Telegram::parse(input).unwrap();
+      telegrams.push(telegram);
+      input = rest;
+    }
+    assert_eq!(telegrams.len(), 3);
+
+    // Only the third segment (CI field 0x12) is marked as the last one.
+    assert!(matches!(MBusDataLinkLayer::next_frame_with_header(&telegrams[..2]), Err(Error::Incomplete(_))));
+
+    let (rest, frame, _) = MBusDataLinkLayer::next_frame_with_header(&telegrams[..]).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(frame, &ENCRYPTED_MESSAGE[..]);
+
+    let (_, _, meta) = Dlms::new(KEY).decrypt_with_meta::<MBusDataLinkLayer, _>(&telegrams[..]).unwrap();
+    assert_eq!(meta.segment_count(), 3);
+
+    // Segments must be consecutive.
+    telegrams.swap(0, 1);
+    assert!(MBusDataLinkLayer::next_frame_with_header(&telegrams[..]).is_err());
+  }
+
+  #[cfg(all(feature = "std", feature = "mbusparse"))]
+  #[test]
+  fn replay_protection() {
+    let bytes = fixtures::segmented_mbus_message(&ENCRYPTED_MESSAGE);
+
+    let mut decoder = DlmsDecoder::<MBusDataLinkLayer>::new(Dlms::new(KEY).with_replay_protection());
+    let mut results = decoder.push(&bytes);
+    results.extend(decoder.push(&bytes));
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(Error::Replay)));
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn replay_protection_ignores_rejected_frames() {
+    const AUTHENTICATION_KEY: [u8; 16] = [0x42; 16];
+
+    let ciphered = |counter, apdu: &[u8]| {
+      fixtures::glo_ciphering_with_authentication(&KEY, Some(&AUTHENTICATION_KEY), [0; 8], counter, apdu).unwrap()
+    };
+
+    let dlms = Dlms::new(KEY).with_authentication_key(AUTHENTICATION_KEY).with_replay_protection();
+
+    // A frame with a forged tag does not advance the window.
+    let mut forged = ciphered(3, &fixtures::energy_notification(3));
+    *forged.last_mut().unwrap() ^= 0x01;
+    assert!(matches!(dlms.decrypt::<RawDataLinkLayer, _>(&forged[..]), Err(Error::DecryptionFailed)));
+
+    // Neither does an authentic frame without registers.
+    let invalid = ciphered(2, &[0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, 0x0a, 0x00]);
+    assert!(matches!(dlms.decrypt::<RawDataLinkLayer, _>(&invalid[..]), Err(Error::InvalidFormat)));
+
+    let (_, obis) = dlms.decrypt::<RawDataLinkLayer, _>(&ciphered(1, &fixtures::energy_notification(1))[..]).unwrap();
+    assert_eq!(obis.get(&codes::ACTIVE_ENERGY_IMPORT).map(|reg| reg.value()), Some(&Data::DoubleLongUnsigned(1)));
+
+    assert!(matches!(
+      dlms.decrypt::<RawDataLinkLayer, _>(&ciphered(1, &fixtures::energy_notification(1))[..]),
+      Err(Error::Replay)
+    ));
+  }
+
+  #[test]
+  fn decrypt_authenticated() {
+    const AUTHENTICATION_KEY: [u8; 16] = [0x42; 16];
+
+    let apdu = fixtures::energy_notification(1234);
+    let ciphered =
+      fixtures::glo_ciphering_with_authentication(&KEY, Some(&AUTHENTICATION_KEY), [0; 8], 1, &apdu).unwrap();
+
+    let (rest, decrypted) = Dlms::new(KEY)
+      .with_authentication_key(AUTHENTICATION_KEY)
+      .decrypt_apdu::<RawDataLinkLayer, _>(&ciphered[..])
+      .unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(decrypted, Apdu::parse(&apdu).unwrap().1);
+
+    // The tag cannot be verified without the authentication key.
+    assert!(matches!(Dlms::new(KEY).decrypt_apdu::<RawDataLinkLayer, _>(&ciphered[..]), Err(Error::DecryptionFailed)));
+    assert!(matches!(
+      Dlms::new(KEY).with_authentication_key([0; 16]).decrypt_apdu::<RawDataLinkLayer, _>(&ciphered[..]),
+      Err(Error::DecryptionFailed)
+    ));
+  }
+
+  #[test]
+  fn decrypt_raw() {
+    let dlms = Dlms::new(KEY);
+
+    let (rest, apdu) = dlms.decrypt_apdu::<RawDataLinkLayer, _>(&ENCRYPTED_MESSAGE[..]).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(apdu, Apdu::parse(&DECRYPTED_MESSAGE).unwrap().1);
+
+    assert!(matches!(dlms.decrypt_apdu::<RawDataLinkLayer, _>(rest), Err(Error::Incomplete(None))));
+
+    let mut too_long = ENCRYPTED_MESSAGE;
+    too_long[12] += 1;
+    assert!(matches!(dlms.decrypt_apdu::<RawDataLinkLayer, _>(&too_long[..]), Err(Error::InvalidFormat)));
+    assert!(matches!(Apdu::parse_encrypted(&too_long, &KEY.into()), Err(nom::Err::Failure(Error::InvalidFormat))));
+
+    let mut suite_2 = ENCRYPTED_MESSAGE;
+    suite_2[13] = 0x22;
+    assert!(matches!(dlms.decrypt_apdu::<RawDataLinkLayer, _>(&suite_2[..]), Err(Error::UnsupportedSuite(2))));
+  }
+
+  #[test]
+  fn decrypt_fixture() {
+    let apdu = fixtures::data_notification(vec![
+      fixtures::register([0, 0, 96, 1, 0, 255], Data::OctetString(b"12345678".to_vec()), None),
+      fixtures::register([1, 0, 1, 8, 0, 255], Data::DoubleLongUnsigned(1234), Some((-1, Unit::WattHour as u8))),
+    ]);
+    let ciphered = fixtures::glo_ciphering(&KEY, *b"KFM\x10\x20\x01\x12\xa9", 42, &apdu).unwrap();
+
+    let (_, obis_map, meta) = Dlms::new(KEY).decrypt_with_meta::<RawDataLinkLayer, _>(&ciphered[..]).unwrap();
+    assert_eq!(meta.invocation_counter(), Some(42));
+    assert_eq!(obis_map.len(), 2);
+    assert_eq!(obis_map[&ObisCode::new(1, 0, 1, 8, 0, 255)].value(), &Data::Float64(123.4));
+  }
+
+  #[test]
+  fn decrypt_without_system_title() {
+    let system_title = *b"KFM\x10\x20\x01\x12\xa9";
+    let apdu = fixtures::data_notification(vec![]);
+    let ciphered = fixtures::glo_ciphering(&KEY, system_title, 42, &apdu).unwrap();
+
+    // Replace the system title with an empty one.
+    let mut bytes = vec![0xdb, 0x00];
+    bytes.extend(&ciphered[10..]);
+
+    let mut ciphering = match Apdu::parse(&bytes).unwrap().1 {
+      Apdu::GeneralGloCiphering(ciphering) => ciphering,
+      apdu => panic!("unexpected APDU: {:?}", apdu),
+    };
+    assert_eq!(ciphering.system_title(), None);
+    assert_eq!(ciphering.initialization_vector(), None);
+    assert!(ciphering.clone().decrypt(&KEY.into()).is_err());
+
+    ciphering.set_system_title(system_title);
+    assert_eq!(ciphering.decrypt(&KEY.into()).unwrap(), apdu);
+  }
+
+  #[test]
+  fn decrypt_with_one_byte_long_form_length() {
+    let apdu = fixtures::data_notification(vec![Data::OctetString(vec![0; 150])]);
+    let ciphered = fixtures::glo_ciphering(&KEY, *b"KFM\x10\x20\x01\x12\xa9", 42, &apdu).unwrap();
+    assert_eq!(ciphered[10], 0x81);
+
+    let (rest, decrypted) = Dlms::new(KEY).decrypt_apdu::<RawDataLinkLayer, _>(&ciphered[..]).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(decrypted, Apdu::parse(&apdu).unwrap().1);
+  }
+
+  #[test]
+  fn decrypt_wrapper() {
+    let mut bytes = vec![0x00, 0x01, 0x00, 0x01, 0x00, 0x10];
+    bytes.extend((ENCRYPTED_MESSAGE.len() as u16).to_be_bytes());
+    bytes.extend(&ENCRYPTED_MESSAGE[..]);
+
+    let dlms = Dlms::new(KEY);
+
+    let (rest, _) = dlms.decrypt_apdu::<WrapperDataLinkLayer, _>(&bytes[..]).unwrap();
+    assert!(rest.is_empty());
+
+    let (_, _, header) = WrapperDataLinkLayer::next_frame_with_header(&bytes[..]).unwrap();
+    assert_eq!(
+      header.address(),
+      wrapper::WrapperAddress {
+        source: wrapper::WrapperAddress::MANAGEMENT_LOGICAL_DEVICE,
+        destination: wrapper::WrapperAddress::PUBLIC_CLIENT,
+      }
+    );
+
+    assert!(matches!(
+      dlms.decrypt_apdu::<WrapperDataLinkLayer, _>(&bytes[..(bytes.len() - 1)]),
+      Err(Error::Incomplete(_))
+    ));
+  }
+
+  #[test]
+  fn decrypt_incomplete_needed() {
+    let needed = |result: Result<_, Error>| match result {
+      Err(Error::Incomplete(needed)) => needed.map(NonZeroUsize::get),
+      result => panic!("unexpected result: {:?}", result),
+    };
+
+    let dlms = Dlms::new(KEY);
+
+    // The length prefix of the wrapper header tells how many bytes are missing.
+    let mut bytes = vec![0x00, 0x01, 0x00, 0x01, 0x00, 0x10];
+    bytes.extend((ENCRYPTED_MESSAGE.len() as u16).to_be_bytes());
+    bytes.extend(&ENCRYPTED_MESSAGE[..]);
+    let truncated = &bytes[..(bytes.len() - 2)];
+    assert_eq!(needed(dlms.decrypt_apdu::<WrapperDataLinkLayer, _>(truncated).map(|_| ())), Some(2));
+    assert_eq!(needed(dlms.decrypt_apdu::<WrapperDataLinkLayer, _>(&bytes[..5]).map(|_| ())), Some(1));
+  }
+
+  #[test]
+  fn test_parse() {
+    let decrypted = Apdu::parse_encrypted(&ENCRYPTED_MESSAGE, &KEY.into()).unwrap().1;
+    assert_eq!(parse(&ENCRYPTED_MESSAGE, Some(&KEY.into())).unwrap(), decrypted);
+    assert!(matches!(parse(&ENCRYPTED_MESSAGE, None).unwrap(), Apdu::GeneralGloCiphering(_)));
+  }
+
+  #[test]
+  fn test_parse_encrypted_with_envelope() {
+    let decrypted = Apdu::parse_encrypted(&ENCRYPTED_MESSAGE, &KEY.into()).unwrap().1;
+
+    let (_, (envelope, apdu)) = Apdu::parse_encrypted_with_envelope(&ENCRYPTED_MESSAGE, &KEY.into()).unwrap();
+    assert_eq!(apdu, decrypted);
+    assert_eq!(envelope.system_title(), Some(&[0x4b, 0x46, 0x4d, 0x10, 0x20, 0x01, 0x12, 0xa9]));
+    assert_eq!(envelope.invocation_counter(), Some(0x0002bc66));
+  }
 
   #[test]
   fn test_parse_mbus() {
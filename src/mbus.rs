@@ -1,28 +1,133 @@
-use core::convert::TryFrom;
+use core::{convert::TryFrom, num::NonZeroUsize};
 
 use crate::{
   control_information::{ControlInformation, HeaderType},
-  map_nom_error, DlmsDataLinkLayer, Error,
+  map_nom_error, Apdu, DlmsDataLinkLayer, DlmsDataLinkLayerHeader, Error, StreamingDataLinkLayer,
 };
 
 use alloc::{borrow::Cow, vec::Vec};
 use mbusparse::Telegram;
-use nom::{sequence::tuple, IResult};
+use nom::{
+  combinator::{all_consuming, complete, map},
+  number::complete::{le_u16, le_u32, u8},
+  sequence::tuple,
+  IResult,
+};
 
 #[derive(Debug)]
 pub enum MBusDataLinkLayer {}
 
-fn parse_mbus<'i, 'f>(input: &'f [Telegram<'i>]) -> IResult<&'f [Telegram<'i>], Cow<'i, [u8]>, Error> {
-  let mut payload = Vec::new();
+/// Identification of an M-Bus device, as contained in a long transport layer header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MBusAddress {
+  pub(crate) identification_number: u32,
+  pub(crate) manufacturer: u16,
+  pub(crate) version: u8,
+  pub(crate) device_type: u8,
+}
+
+impl MBusAddress {
+  /// The identification number, decoded from its 8 BCD digits.
+  pub fn identification_number(&self) -> u32 {
+    self.identification_number
+  }
+
+  /// The manufacturer ID, i.e. three letters packed into 5 bits each.
+  pub fn manufacturer(&self) -> u16 {
+    self.manufacturer
+  }
+
+  pub fn version(&self) -> u8 {
+    self.version
+  }
+
+  pub fn device_type(&self) -> u8 {
+    self.device_type
+  }
+
+  /// The system title derived from this address, i.e. the manufacturer, identification
+  /// number, version and device type as encoded in a wireless M-Bus link layer header.
+  pub fn system_title(&self) -> [u8; 8] {
+    let mut system_title = [0; 8];
+    system_title[0..2].copy_from_slice(&self.manufacturer.to_le_bytes());
+    system_title[2..6].copy_from_slice(&encode_bcd(self.identification_number).to_le_bytes());
+    system_title[6] = self.version;
+    system_title[7] = self.device_type;
+    system_title
+  }
+
+  fn parse(input: &[u8]) -> IResult<&[u8], Self, Error> {
+    let (input, (identification_number, manufacturer, version, device_type)) = tuple((le_u32, le_u16, u8, u8))(input)?;
+
+    let identification_number = decode_bcd(identification_number).ok_or(nom::Err::Failure(Error::InvalidFormat))?;
+
+    Ok((input, Self { identification_number, manufacturer, version, device_type }))
+  }
+}
+
+fn decode_bcd(bcd: u32) -> Option<u32> {
+  (0..8).rev().try_fold(0, |n, i| match (bcd >> (i * 4)) & 0xf {
+    digit @ 0..=9 => Some(n * 10 + digit),
+    _ => None,
+  })
+}
+
+fn encode_bcd(n: u32) -> u32 {
+  (0..8).fold(0, |bcd, i| bcd | ((n / 10u32.pow(i)) % 10) << (i * 4))
+}
+
+/// M-Bus transport layer header of an unsegmented frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MBusHeader {
+  pub(crate) address: Option<MBusAddress>,
+  pub(crate) access_number: u8,
+  pub(crate) status: u8,
+  pub(crate) configuration: u16,
+}
+
+impl MBusHeader {
+  /// The device address, only present in a long header.
+  pub fn address(&self) -> Option<&MBusAddress> {
+    self.address.as_ref()
+  }
+
+  pub fn access_number(&self) -> u8 {
+    self.access_number
+  }
+
+  pub fn status(&self) -> u8 {
+    self.status
+  }
+
+  /// The configuration word, which includes the encryption mode.
+  pub fn configuration(&self) -> u16 {
+    self.configuration
+  }
+
+  fn parse(input: &[u8], header: HeaderType) -> IResult<&[u8], Self, Error> {
+    let (input, address) = match header {
+      HeaderType::Long => map(MBusAddress::parse, Some)(input)?,
+      HeaderType::Short => (input, None),
+    };
+    let (input, (access_number, status, configuration)) = tuple((u8, u8, le_u16))(input)?;
+
+    Ok((input, Self { address, access_number, status, configuration }))
+  }
+}
+
+type MBusFrame<'i, 'f> = (&'f [Telegram<'i>], Cow<'i, [u8]>, Option<MBusHeader>);
+
+/// Parse the segments of the next frame, passing the user data of each segment to `push`.
+fn parse_mbus_segments<'i, 'f>(
+  input: &'f [Telegram<'i>],
+  mut push: impl FnMut(&'i [u8]),
+) -> IResult<&'f [Telegram<'i>], Option<MBusHeader>, Error> {
   let mut current_segment = 0;
-  let mut len = 0;
 
-  for telegram in input {
+  for (i, telegram) in input.iter().enumerate() {
     match telegram {
       Telegram::LongFrame { control_information, user_data, .. } => {
-        use nom::number::complete::u8;
-
-        let user_data: &[u8] = user_data;
+        let user_data: &'i [u8] = user_data;
 
         let control_information =
           ControlInformation::try_from(*control_information).map_err(|_| nom::Err::Failure(Error::InvalidFormat))?;
@@ -36,38 +141,192 @@ fn parse_mbus<'i, 'f>(input: &'f [Telegram<'i>]) -> IResult<&'f [Telegram<'i>],
 
             (user_data, last_segment)
           },
-          ControlInformation::Unsegmented { header, .. } => {
-            let (user_data, _ala) = if header == HeaderType::Long {
-              let (user_data, (m_id, ver, dt)) = tuple((u8, u8, u8))(user_data)?;
-              (user_data, Some((m_id, ver, dt)))
-            } else {
-              (user_data, None)
-            };
-
-            let (user_data, (_acc, _sts, _cfg)) = tuple((u8, u8, u8))(user_data)?;
+          ControlInformation::Unsegmented { header } => {
+            let (user_data, header) = MBusHeader::parse(user_data, header)?;
+            push(user_data);
 
-            return Ok((&input[len..], Cow::from(user_data)))
+            return Ok((&input[(i + 1)..], Some(header)))
           },
         };
 
         let (user_data, (_stsap, _dtsap)) = tuple((u8, u8))(user_data)?;
-
-        payload.extend(user_data);
-        len += 1;
+        push(user_data);
 
         if last_segment {
-          return Ok((&input[len..], Cow::from(payload)))
+          return Ok((&input[(i + 1)..], None))
         }
       },
-      _ => return Err(nom::Err::Failure(Error::InvalidFormat)),
+      // Short frames, control frames and single character acknowledgements carry no data.
+      _ => continue,
     }
   }
 
   Err(nom::Err::Incomplete(nom::Needed::Unknown))
 }
 
+#[allow(clippy::type_complexity)]
+fn parse_mbus<'i, 'f>(
+  input: &'f [Telegram<'i>],
+) -> IResult<&'f [Telegram<'i>], (Cow<'i, [u8]>, Option<MBusHeader>, usize), Error> {
+  // Only allocate if there is more than one segment.
+  let mut payload = Cow::Borrowed(&[][..]);
+  let mut segment_count = 0;
+
+  let (input, header) = parse_mbus_segments(input, |user_data| {
+    if payload.is_empty() {
+      payload = Cow::Borrowed(user_data);
+    } else {
+      payload.to_mut().extend(user_data);
+    }
+    segment_count += 1;
+  })?;
+
+  Ok((input, (payload, header, segment_count)))
+}
+
+impl MBusDataLinkLayer {
+  /// Like [`next_frame_with_header`](DlmsDataLinkLayerHeader::next_frame_with_header), but skip frames
+  /// whose long header addresses a device other than `identification_number`.
+  ///
+  /// Frames without a long header cannot be attributed to a device and are always returned.
+  pub fn next_frame_for_device<'i, 'f>(
+    mut input: &'f [Telegram<'i>],
+    identification_number: u32,
+  ) -> Result<MBusFrame<'i, 'f>, Error> {
+    loop {
+      let (rest, (frame, header, _)) = map_nom_error(parse_mbus(input))?;
+
+      match header.as_ref().and_then(|header| header.address()) {
+        Some(address) if address.identification_number() != identification_number => input = rest,
+        _ => return Ok((rest, frame, header)),
+      }
+    }
+  }
+}
+
 impl<'i, 'f> DlmsDataLinkLayer<'i, &'f [Telegram<'i>]> for MBusDataLinkLayer {
   fn next_frame(input: &'f [Telegram<'i>]) -> Result<(&'f [Telegram<'i>], Cow<'i, [u8]>), Error> {
-    map_nom_error(parse_mbus(input))
+    let (input, (frame, _, _)) = map_nom_error(parse_mbus(input))?;
+    Ok((input, frame))
+  }
+
+  fn next_frame_with_segment_count(
+    input: &'f [Telegram<'i>],
+  ) -> Result<(&'f [Telegram<'i>], Cow<'i, [u8]>, usize), Error> {
+    let (input, (frame, _, segment_count)) = map_nom_error(parse_mbus(input))?;
+    Ok((input, frame, segment_count))
+  }
+
+  fn next_frame_into(input: &'f [Telegram<'i>], buffer: &mut Vec<u8>) -> Result<&'f [Telegram<'i>], Error> {
+    buffer.clear();
+    let (input, _) = map_nom_error(parse_mbus_segments(input, |user_data| buffer.extend_from_slice(user_data)))?;
+    Ok(input)
+  }
+}
+
+impl<'i, 'f> DlmsDataLinkLayerHeader<'i, &'f [Telegram<'i>]> for MBusDataLinkLayer {
+  type Header = Option<MBusHeader>;
+
+  fn next_frame_with_header(input: &'f [Telegram<'i>]) -> Result<MBusFrame<'i, 'f>, Error> {
+    let (input, (frame, header, _)) = map_nom_error(parse_mbus(input))?;
+    Ok((input, frame, header))
+  }
+
+  fn system_title(header: &Self::Header) -> Option<[u8; 8]> {
+    Some(header.as_ref()?.address()?.system_title())
+  }
+}
+
+/// The CRC used for the blocks of a wireless M-Bus frame, as specified in EN 13757-4.
+pub(crate) fn block_crc(data: &[u8]) -> u16 {
+  let crc = data.iter().fold(0u16, |crc, &b| {
+    (0..8).fold(crc ^ ((b as u16) << 8), |crc, _| if crc & 0x8000 != 0 { (crc << 1) ^ 0x3d65 } else { crc << 1 })
+  });
+  !crc
+}
+
+/// Verify and remove the block CRCs of a wireless M-Bus frame in format A.
+///
+/// The first block contains the L-, C-, M- and A-fields, each following block up to 16 bytes
+/// of data, and every block is followed by its big-endian CRC. Only use this for frames which
+/// still contain their CRCs, since most receivers already strip them.
+///
+/// Returns [`Error::ChecksumMismatch`] if any CRC is wrong, [`Error::Incomplete`] if `frame`
+/// is shorter than its L-field indicates and [`Error::InvalidFormat`] if it is longer.
+pub fn strip_block_crcs(frame: &[u8]) -> Result<Vec<u8>, Error> {
+  const FIRST_BLOCK_LEN: usize = 10;
+  const BLOCK_LEN: usize = 16;
+
+  let (_, len) = map_nom_error(nom::number::streaming::u8(frame))?;
+  if (len as usize) < FIRST_BLOCK_LEN - 1 {
+    return Err(Error::InvalidFormat)
+  }
+  let len = len as usize + 1;
+
+  let blocks = 1 + (len - FIRST_BLOCK_LEN).div_ceil(BLOCK_LEN);
+  let expected_len = len + blocks * 2;
+  if frame.len() < expected_len {
+    return Err(Error::Incomplete(NonZeroUsize::new(expected_len - frame.len())))
+  } else if frame.len() > expected_len {
+    return Err(Error::InvalidFormat)
+  }
+
+  let mut data = Vec::with_capacity(len);
+  let mut input = frame;
+  while !input.is_empty() {
+    let block_len = if data.is_empty() { FIRST_BLOCK_LEN } else { (len - data.len()).min(BLOCK_LEN) };
+    let (block, rest) = input.split_at(block_len);
+    let (crc, rest) = rest.split_at(2);
+
+    if block_crc(block).to_be_bytes() != crc {
+      return Err(Error::ChecksumMismatch)
+    }
+
+    data.extend_from_slice(block);
+    input = rest;
+  }
+
+  Ok(data)
+}
+
+/// Parse the next plaintext APDU from M-Bus `telegrams` without needing a key.
+///
+/// Returns [`Error::KeyRequired`] if the APDU is encrypted.
+pub fn parse_mbus_plaintext<'i, 'f>(telegrams: &'f [Telegram<'i>]) -> Result<(&'f [Telegram<'i>], Apdu), Error> {
+  let (telegrams, (frame, _, _)) = map_nom_error(parse_mbus(telegrams))?;
+  let (_, apdu) = all_consuming(complete(Apdu::parse))(&frame).map_err(|_| Error::InvalidFormat)?;
+
+  match apdu {
+    Apdu::GeneralGloCiphering(_) => Err(Error::KeyRequired),
+    apdu => Ok((telegrams, apdu)),
+  }
+}
+
+impl<'i, 'f> TryFrom<&'f [Telegram<'i>]> for Apdu {
+  type Error = Error;
+
+  fn try_from(telegrams: &'f [Telegram<'i>]) -> Result<Self, Self::Error> {
+    let (_, apdu) = parse_mbus_plaintext(telegrams)?;
+    Ok(apdu)
+  }
+}
+
+impl StreamingDataLinkLayer for MBusDataLinkLayer {
+  type Frame<'i> = Telegram<'i>;
+
+  fn parse_frame(input: &[u8]) -> Result<(&[u8], Telegram<'_>), Error> {
+    Telegram::parse(input).map_err(|err| match err {
+      mbusparse::Error::Incomplete(needed) => Error::Incomplete(needed),
+      mbusparse::Error::ChecksumMismatch => Error::ChecksumMismatch,
+      _ => Error::InvalidFormat,
+    })
+  }
+
+  fn is_frame_start(byte: u8) -> bool {
+    matches!(byte, 0x10 | 0x68 | 0xe5)
+  }
+
+  fn has_user_data(frame: &Telegram<'_>) -> bool {
+    matches!(frame, Telegram::LongFrame { .. })
   }
 }
@@ -7,7 +7,7 @@ use serde::{Serialize, Serializer};
 
 use super::*;
 
-#[derive(Clone, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct ObisCode {
   a: u8,
   b: u8,
@@ -22,6 +22,7 @@ impl ObisCode {
     Self { a, b, c, d, e, f }
   }
 
+  /// Parse an OBIS code from exactly six bytes, leaving any trailing bytes to the caller.
   pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
     let (input, (a, b, c, d, e, f)) = tuple((u8, u8, u8, u8, u8, u8))(input)?;
     Ok((input, Self::new(a, b, c, d, e, f)))
@@ -0,0 +1,265 @@
+use alloc::collections::btree_map::{self, BTreeMap};
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+
+use nom::{combinator::fail, IResult};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::data_notification::DataNotification;
+use crate::{Apdu, Data, DateTime, ObisCode, Register};
+#[cfg(feature = "serde")]
+use crate::{DefaultUnitFormatter, UnitFormatter, WithUnitFormatter};
+
+/// How many levels of structures wrapping multiple registers are flattened by [`ObisMap::parse`].
+const MAX_REGISTER_NESTING: usize = 4;
+
+/// How to resolve registers present in both maps when using [`ObisMap::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+  /// Keep the registers of the map with the later date-time.
+  ///
+  /// If the date-times are missing or cannot be compared, the merged map is considered newer.
+  KeepNewest,
+  /// Keep the existing registers.
+  KeepExisting,
+  /// Replace existing registers with the merged ones.
+  Overwrite,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ObisMap {
+  pub(crate) map: BTreeMap<ObisCode, Register>,
+  pub(crate) date_time: Option<DateTime>,
+}
+
+impl Deref for ObisMap {
+  type Target = BTreeMap<ObisCode, Register>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.map
+  }
+}
+
+impl DerefMut for ObisMap {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.map
+  }
+}
+
+impl FromIterator<Register> for ObisMap {
+  fn from_iter<T: IntoIterator<Item = Register>>(iter: T) -> Self {
+    let mut map = Self::new();
+    map.extend(iter);
+    map
+  }
+}
+
+impl Extend<Register> for ObisMap {
+  fn extend<T: IntoIterator<Item = Register>>(&mut self, iter: T) {
+    for register in iter {
+      self.insert(register);
+    }
+  }
+}
+
+impl IntoIterator for ObisMap {
+  type Item = (ObisCode, Register);
+  type IntoIter = btree_map::IntoIter<ObisCode, Register>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.map.into_iter()
+  }
+}
+
+impl<'a> IntoIterator for &'a ObisMap {
+  type Item = (&'a ObisCode, &'a Register);
+  type IntoIter = btree_map::Iter<'a, ObisCode, Register>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.map.iter()
+  }
+}
+
+impl ObisMap {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Insert a register keyed by its OBIS code, returning the register previously stored for this code.
+  pub fn insert(&mut self, register: Register) -> Option<Register> {
+    self.map.insert(register.obis_code, register)
+  }
+
+  /// The date-time of the notification these registers were parsed from.
+  pub fn date_time(&self) -> Option<&DateTime> {
+    self.date_time.as_ref()
+  }
+
+  /// Merge the registers of `other` into this map, resolving conflicts using the given `policy`.
+  ///
+  /// Regardless of the policy, the merged map keeps the later of both date-times.
+  pub fn merge(&mut self, other: ObisMap, policy: MergePolicy) {
+    let other_is_newer = match (&self.date_time, &other.date_time) {
+      (Some(date_time), Some(other_date_time)) => {
+        date_time.partial_cmp_instant(other_date_time) != Some(core::cmp::Ordering::Greater)
+      },
+      _ => true,
+    };
+
+    if other_is_newer && other.date_time.is_some() {
+      self.date_time = other.date_time;
+    }
+
+    let overwrite = match policy {
+      MergePolicy::KeepNewest => other_is_newer,
+      MergePolicy::KeepExisting => false,
+      MergePolicy::Overwrite => true,
+    };
+
+    if overwrite {
+      self.map.extend(other.map);
+    } else {
+      for (obis_code, register) in other.map {
+        self.map.entry(obis_code).or_insert(register);
+      }
+    }
+  }
+
+  /// Iterate over the values of all numeric registers whose OBIS code matches `pattern`, converted to `f64`.
+  ///
+  /// Values are converted using [`Data::as_f64`], non-numeric registers are skipped.
+  pub fn iter_f64<'a>(&'a self, mut pattern: impl FnMut(&ObisCode) -> bool + 'a) -> impl Iterator<Item = f64> + 'a {
+    self.map.iter().filter(move |(code, _)| pattern(code)).filter_map(|(_, register)| register.value().as_f64())
+  }
+
+  /// Sum the values of all numeric registers whose OBIS code matches `pattern`.
+  ///
+  /// ```
+  /// use dlms_cosem::{Data, ObisCode, ObisMap, Register};
+  ///
+  /// let phases = [ObisCode::new(1, 0, 21, 7, 0, 255), ObisCode::new(1, 0, 41, 7, 0, 255), ObisCode::new(1, 0, 61, 7, 0, 255)];
+  ///
+  /// let map = phases.iter().map(|&code| Register::new(code, Data::DoubleLongUnsigned(100), None)).collect::<ObisMap>();
+  /// assert_eq!(map.sum_f64(|code| phases.contains(code)), 300.0);
+  /// ```
+  pub fn sum_f64(&self, pattern: impl FnMut(&ObisCode) -> bool) -> f64 {
+    self.iter_f64(pattern).sum()
+  }
+
+  /// Serialize this map with the units of its registers formatted using `formatter`.
+  #[cfg(feature = "serde")]
+  pub fn with_unit_formatter<'a, F: UnitFormatter + ?Sized>(
+    &'a self,
+    formatter: &'a F,
+  ) -> WithUnitFormatter<'a, Self, F> {
+    WithUnitFormatter { value: self, formatter }
+  }
+
+  /// Clone all registers into a vector, ordered by their OBIS code.
+  pub fn to_vec(&self) -> Vec<Register> {
+    self.map.values().cloned().collect()
+  }
+
+  /// Move all registers into a vector, ordered by their OBIS code.
+  pub fn into_vec(self) -> Vec<Register> {
+    self.map.into_values().collect()
+  }
+
+  /// Convert the `Data` for a given `ObisCode` using the given function.
+  pub fn convert(&mut self, code: &ObisCode, mut f: impl FnMut(Data) -> Data) {
+    if let Some(reg) = self.map.get_mut(code) {
+      if reg.raw_value.is_none() {
+        reg.raw_value = Some(reg.value.clone());
+      }
+      let value = &mut reg.value;
+      *value = f(mem::replace(value, Data::Null));
+    }
+  }
+
+  /// Parse the registers of a data notification.
+  ///
+  /// A notification without registers, e.g. a keep-alive with an empty or null body, yields an empty map.
+  pub fn parse(input: &Apdu) -> IResult<(), Self> {
+    let (rest, map) = Self::parse_partial(input).map_err(|e| e.map_input(|_| ()))?;
+    if !rest.is_empty() {
+      return fail(())
+    }
+
+    Ok(((), map))
+  }
+
+  /// Like [`parse`](Self::parse), but stop at the first item of the notification body which is
+  /// not a register, returning the remaining items instead of failing.
+  ///
+  /// ```
+  /// use dlms_cosem::{Apdu, Data, ObisMap};
+  ///
+  /// #[rustfmt::skip]
+  /// let bytes = [
+  ///   0x0f, 0x00, 0x00, 0x00, 0x01, 0x00, // Data Notification
+  ///   0x02, 0x03, // Structure
+  ///     0x09, 0x06, 0x01, 0x00, 0x01, 0x08, 0x00, 0xff, // OBIS Code
+  ///     0x06, 0x00, 0x00, 0x04, 0xd2, // Value
+  ///     0x0a, 0x03, 0x53, 0x55, 0x4d, // Trailing Summary
+  /// ];
+  ///
+  /// let (_, apdu) = Apdu::parse(&bytes).unwrap();
+  /// assert!(ObisMap::parse(&apdu).is_err());
+  ///
+  /// let (rest, obis_map) = ObisMap::parse_partial(&apdu).unwrap();
+  /// assert_eq!(obis_map.len(), 1);
+  /// assert_eq!(rest, [Data::VisibleString("SUM".into())]);
+  /// ```
+  pub fn parse_partial(input: &Apdu) -> IResult<&[Data], Self> {
+    let (data, date_time) = match input {
+      Apdu::DataNotification(DataNotification {
+        notification_body: Data::Structure(data) | Data::Array(data),
+        date_time,
+        ..
+      }) => (data.as_slice(), *date_time),
+      Apdu::DataNotification(DataNotification { notification_body: Data::Null, date_time, .. }) => {
+        (&[][..], *date_time)
+      },
+      _ => return fail(&[][..]),
+    };
+
+    let mut values = BTreeMap::new();
+    let (rest, ()) = Register::parse_partial(data, MAX_REGISTER_NESTING, &mut values)?;
+
+    Ok((rest, Self { map: values, date_time }))
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ObisMap {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    self.with_unit_formatter(&DefaultUnitFormatter).serialize(serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<F: UnitFormatter + ?Sized> Serialize for WithUnitFormatter<'_, ObisMap, F> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    #[derive(Serialize)]
+    struct Entry<'a> {
+      value: &'a Data,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      unit: Option<&'a str>,
+    }
+
+    let mut map = serializer.serialize_map(Some(self.value.map.len()))?;
+    for (k, v) in self.value.map.iter() {
+      map.serialize_entry(k, &Entry { value: v.value(), unit: v.unit().and_then(|&u| self.formatter.format(u)) })?;
+    }
+    map.end()
+  }
+}
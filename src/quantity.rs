@@ -0,0 +1,79 @@
+use uom::si::f64::{
+  ElectricCharge, ElectricCurrent, ElectricPotential, Energy, Frequency, Length, Mass, Power, Pressure,
+  ThermodynamicTemperature, Time, Volume, VolumeRate,
+};
+use uom::si::{
+  electric_charge, electric_current, electric_potential, energy, frequency, length, mass, power, pressure,
+  thermodynamic_temperature, time, volume, volume_rate,
+};
+
+use crate::{Data, Unit};
+
+/// A numeric value together with its unit, as a [`uom`] quantity.
+///
+/// Only units with an unambiguous SI quantity are supported, e.g. reactive and apparent power
+/// (`var` and `VA`) are not, since `uom` cannot distinguish them from active power.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Quantity {
+  ElectricCharge(ElectricCharge),
+  ElectricCurrent(ElectricCurrent),
+  ElectricPotential(ElectricPotential),
+  Energy(Energy),
+  Frequency(Frequency),
+  Length(Length),
+  Mass(Mass),
+  Power(Power),
+  Pressure(Pressure),
+  ThermodynamicTemperature(ThermodynamicTemperature),
+  Time(Time),
+  Volume(Volume),
+  VolumeRate(VolumeRate),
+}
+
+impl Quantity {
+  /// Convert a numeric `value` in the given `unit` into a quantity.
+  ///
+  /// Returns `None` if the value is not numeric or the unit is not supported.
+  ///
+  /// ```
+  /// use dlms_cosem::{Data, Quantity, Unit};
+  /// use uom::si::energy::kilowatt_hour;
+  ///
+  /// let energy = match Quantity::new(&Data::DoubleLongUnsigned(1500), Unit::WattHour) {
+  ///   Some(Quantity::Energy(energy)) => energy,
+  ///   quantity => panic!("unexpected quantity: {:?}", quantity),
+  /// };
+  /// assert_eq!(energy.get::<kilowatt_hour>(), 1.5);
+  /// ```
+  #[rustfmt::skip]
+  pub fn new(value: &Data, unit: Unit) -> Option<Self> {
+    let v = value.as_f64()?;
+
+    Some(match unit {
+      Unit::Second             => Self::Time(Time::new::<time::second>(v)),
+      Unit::Minute             => Self::Time(Time::new::<time::minute>(v)),
+      Unit::Hour               => Self::Time(Time::new::<time::hour>(v)),
+      Unit::Day                => Self::Time(Time::new::<time::day>(v)),
+      Unit::DegreeCelsius      => Self::ThermodynamicTemperature(ThermodynamicTemperature::new::<thermodynamic_temperature::degree_celsius>(v)),
+      Unit::Kelvin             => Self::ThermodynamicTemperature(ThermodynamicTemperature::new::<thermodynamic_temperature::kelvin>(v)),
+      Unit::Meter              => Self::Length(Length::new::<length::meter>(v)),
+      Unit::CubicMeter         => Self::Volume(Volume::new::<volume::cubic_meter>(v)),
+      Unit::Liter              => Self::Volume(Volume::new::<volume::liter>(v)),
+      Unit::CubicMeterPerHour  => Self::VolumeRate(VolumeRate::new::<volume_rate::cubic_meter_per_hour>(v)),
+      Unit::CubicMeterPerDay   => Self::VolumeRate(VolumeRate::new::<volume_rate::cubic_meter_per_second>(v / 86400.0)),
+      Unit::Kilogramm          => Self::Mass(Mass::new::<mass::kilogram>(v)),
+      Unit::Pascal             => Self::Pressure(Pressure::new::<pressure::pascal>(v)),
+      Unit::Bar                => Self::Pressure(Pressure::new::<pressure::bar>(v)),
+      Unit::Joule              => Self::Energy(Energy::new::<energy::joule>(v)),
+      Unit::WattHour           => Self::Energy(Energy::new::<energy::watt_hour>(v)),
+      Unit::Watt               => Self::Power(Power::new::<power::watt>(v)),
+      Unit::Ampere             => Self::ElectricCurrent(ElectricCurrent::new::<electric_current::ampere>(v)),
+      Unit::Coulomb            => Self::ElectricCharge(ElectricCharge::new::<electric_charge::coulomb>(v)),
+      Unit::AmpereHour         => Self::ElectricCharge(ElectricCharge::new::<electric_charge::ampere_hour>(v)),
+      Unit::Volt               => Self::ElectricPotential(ElectricPotential::new::<electric_potential::volt>(v)),
+      Unit::Hertz              => Self::Frequency(Frequency::new::<frequency::hertz>(v)),
+      _ => return None,
+    })
+  }
+}
@@ -0,0 +1,19 @@
+use alloc::borrow::Cow;
+
+use crate::{DlmsDataLinkLayer, Error};
+
+/// Data link layer for frames which have already been extracted from their transport.
+///
+/// The whole input is returned as a single frame.
+#[derive(Debug)]
+pub enum RawDataLinkLayer {}
+
+impl<'i> DlmsDataLinkLayer<'i, &'i [u8]> for RawDataLinkLayer {
+  fn next_frame(input: &'i [u8]) -> Result<(&'i [u8], Cow<'i, [u8]>), Error> {
+    if input.is_empty() {
+      return Err(Error::Incomplete(None))
+    }
+
+    Ok((&input[input.len()..], Cow::Borrowed(input)))
+  }
+}
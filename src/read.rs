@@ -0,0 +1,130 @@
+use alloc::vec::Vec;
+
+use nom::{
+  combinator::fail,
+  multi::{length_count, length_data},
+  number::streaming::{be_u16, u8},
+  IResult,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{data::parse_length, Data, DataAccessResult};
+
+fn parse_data_block(input: &[u8]) -> IResult<&[u8], (bool, u16, Vec<u8>)> {
+  let (input, last_block) = u8(input)?;
+  let (input, block_number) = be_u16(input)?;
+  let (input, raw_data) = length_data(parse_length)(input)?;
+  Ok((input, (last_block != 0, block_number, raw_data.to_vec())))
+}
+
+/// A reference to a variable when using short name (SN) referencing.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum VariableAccessSpecification {
+  VariableName(u16),
+  ParameterizedAccess { variable_name: u16, selector: u8, parameter: Data },
+  BlockNumberAccess { block_number: u16 },
+  ReadDataBlockAccess { last_block: bool, block_number: u16, raw_data: Vec<u8> },
+}
+
+impl VariableAccessSpecification {
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, choice) = u8(input)?;
+    match choice {
+      2 => {
+        let (input, variable_name) = be_u16(input)?;
+        Ok((input, Self::VariableName(variable_name)))
+      },
+      4 => {
+        let (input, variable_name) = be_u16(input)?;
+        let (input, selector) = u8(input)?;
+        let (input, parameter) = Data::parse(input)?;
+        Ok((input, Self::ParameterizedAccess { variable_name, selector, parameter }))
+      },
+      5 => {
+        let (input, block_number) = be_u16(input)?;
+        Ok((input, Self::BlockNumberAccess { block_number }))
+      },
+      6 => {
+        let (input, (last_block, block_number, raw_data)) = parse_data_block(input)?;
+        Ok((input, Self::ReadDataBlockAccess { last_block, block_number, raw_data }))
+      },
+      _ => fail(input),
+    }
+  }
+}
+
+/// A `ReadRequest` APDU, used with short name (SN) referencing.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadRequest {
+  pub(crate) variable_access_specifications: Vec<VariableAccessSpecification>,
+}
+
+impl ReadRequest {
+  pub fn variable_access_specifications(&self) -> &[VariableAccessSpecification] {
+    &self.variable_access_specifications
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, variable_access_specifications) =
+      length_count(parse_length, VariableAccessSpecification::parse)(input)?;
+    Ok((input, Self { variable_access_specifications }))
+  }
+}
+
+/// The result of reading a single variable.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ReadResult {
+  Data(Data),
+  DataAccessError(DataAccessResult),
+  DataBlockResult { last_block: bool, block_number: u16, raw_data: Vec<u8> },
+  BlockNumber(u16),
+}
+
+impl ReadResult {
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, choice) = u8(input)?;
+    match choice {
+      0 => {
+        let (input, data) = Data::parse(input)?;
+        Ok((input, Self::Data(data)))
+      },
+      1 => {
+        let (input, result) = DataAccessResult::parse(input)?;
+        Ok((input, Self::DataAccessError(result)))
+      },
+      2 => {
+        let (input, (last_block, block_number, raw_data)) = parse_data_block(input)?;
+        Ok((input, Self::DataBlockResult { last_block, block_number, raw_data }))
+      },
+      3 => {
+        let (input, block_number) = be_u16(input)?;
+        Ok((input, Self::BlockNumber(block_number)))
+      },
+      _ => fail(input),
+    }
+  }
+}
+
+/// A `ReadResponse` APDU, used with short name (SN) referencing.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadResponse {
+  pub(crate) results: Vec<ReadResult>,
+}
+
+impl ReadResponse {
+  pub fn results(&self) -> &[ReadResult] {
+    &self.results
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, results) = length_count(parse_length, ReadResult::parse)(input)?;
+    Ok((input, Self { results }))
+  }
+}
@@ -0,0 +1,94 @@
+use alloc::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+
+use crate::{decoder::StreamingDataLinkLayer, Dlms, DlmsDecoder, Error, ObisMap};
+
+/// Decoder for DLMS messages read from a [`Read`] implementation, e.g. a file or socket.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "mbusparse")]
+/// # fn main() -> std::io::Result<()> {
+/// use std::fs::File;
+///
+/// use dlms_cosem::{mbus::MBusDataLinkLayer, Dlms, DlmsReader};
+///
+/// let key = [0; 16];
+/// let file = File::open("capture.bin")?;
+///
+/// for obis in DlmsReader::<_, MBusDataLinkLayer>::new(file, Dlms::new(key)) {
+///   println!("{:?}", obis);
+/// }
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "mbusparse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct DlmsReader<R, Dll> {
+  reader: R,
+  decoder: DlmsDecoder<Dll>,
+  pending: VecDeque<Result<ObisMap, Error>>,
+  io_error: Option<std::io::Error>,
+  done: bool,
+}
+
+impl<R, Dll> DlmsReader<R, Dll>
+where
+  R: Read,
+  Dll: StreamingDataLinkLayer,
+{
+  pub fn new(reader: R, dlms: Dlms) -> Self {
+    Self { reader, decoder: DlmsDecoder::new(dlms), pending: VecDeque::new(), io_error: None, done: false }
+  }
+
+  /// The error which caused the last [`Error::Io`].
+  pub fn io_error(&self) -> Option<&std::io::Error> {
+    self.io_error.as_ref()
+  }
+
+  /// Consume this `DlmsReader` and return the underlying reader.
+  pub fn into_inner(self) -> R {
+    self.reader
+  }
+}
+
+impl<R, Dll> Iterator for DlmsReader<R, Dll>
+where
+  R: Read,
+  Dll: StreamingDataLinkLayer,
+{
+  type Item = Result<ObisMap, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut buf = [0; 1024];
+
+    loop {
+      if let Some(result) = self.pending.pop_front() {
+        return Some(result)
+      }
+
+      if self.done {
+        return None
+      }
+
+      match self.reader.read(&mut buf) {
+        Ok(0) => {
+          self.done = true;
+
+          if self.decoder.has_pending_input() {
+            return Some(Err(Error::Incomplete(None)))
+          }
+        },
+        Ok(n) => self.pending.extend(self.decoder.push(&buf[..n])),
+        Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+        Err(err) => {
+          self.done = true;
+          self.io_error = Some(err);
+          return Some(Err(Error::Io))
+        },
+      }
+    }
+  }
+}
@@ -0,0 +1,309 @@
+use alloc::collections::btree_map::BTreeMap;
+use core::convert::TryFrom;
+
+use nom::{
+  branch::alt,
+  combinator::{all_consuming, complete, fail},
+  IResult,
+};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+#[cfg(feature = "uom")]
+use crate::Quantity;
+use crate::{Data, DataAccessResult, Error, ObisCode, Unit};
+#[cfg(feature = "serde")]
+use crate::{DefaultUnitFormatter, UnitFormatter, WithUnitFormatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+  pub(crate) obis_code: ObisCode,
+  pub(crate) value: Data,
+  /// The value as transmitted, only stored if it differs from `value`.
+  pub(crate) raw_value: Option<Data>,
+  scaler: Option<i8>,
+  unit: Option<Unit>,
+  unit_code: Option<u8>,
+}
+
+impl Register {
+  pub fn new(obis_code: ObisCode, value: Data, unit: Option<Unit>) -> Self {
+    Self { obis_code, value, raw_value: None, scaler: None, unit, unit_code: unit.map(|unit| unit as u8) }
+  }
+
+  /// Create a register from a raw value and its scaler, scaling it like a parsed register.
+  ///
+  /// ```
+  /// use dlms_cosem::{Data, ObisCode, Register, Unit};
+  ///
+  /// let register = Register::with_scaler(ObisCode::new(1, 0, 1, 8, 0, 255), Data::DoubleLongUnsigned(1234), -1, Unit::WattHour);
+  /// assert_eq!(register.value(), &Data::Float64(123.4));
+  /// assert_eq!(register.raw_value(), &Data::DoubleLongUnsigned(1234));
+  /// assert_eq!(register.unit(), Some(&Unit::WattHour));
+  /// ```
+  pub fn with_scaler(obis_code: ObisCode, value: Data, scaler: i8, unit: Unit) -> Self {
+    let (value, raw_value) = Self::scale(value, scaler);
+    Self { obis_code, value, raw_value, scaler: Some(scaler), unit: Some(unit), unit_code: Some(unit as u8) }
+  }
+
+  /// Scale `value`, also returning the raw value if it differs from the scaled one.
+  ///
+  /// Values whose scaled result is not representable are left unscaled, see [`Data::scaled`].
+  fn scale(value: Data, scaler: i8) -> (Data, Option<Data>) {
+    // Some meters use the lowest scaler as a marker for registers which are not scaled.
+    if scaler == i8::MIN {
+      return (value, None)
+    }
+
+    let scaled = value.scaled(scaler);
+    if scaled == value {
+      (value, None)
+    } else {
+      (scaled, Some(value))
+    }
+  }
+
+  pub fn obis_code(&self) -> &ObisCode {
+    &self.obis_code
+  }
+
+  pub fn value(&self) -> &Data {
+    &self.value
+  }
+
+  /// The value as transmitted, before applying the scaler.
+  pub fn raw_value(&self) -> &Data {
+    self.raw_value.as_ref().unwrap_or(&self.value)
+  }
+
+  /// The scaler, i.e. the power of ten the raw value is multiplied with, if one was transmitted.
+  pub fn scaler(&self) -> Option<i8> {
+    self.scaler
+  }
+
+  pub fn unit(&self) -> Option<&Unit> {
+    self.unit.as_ref()
+  }
+
+  /// The scaled value together with its unit as a [`Quantity`], see [`Quantity::new`].
+  #[cfg(feature = "uom")]
+  pub fn quantity(&self) -> Option<Quantity> {
+    Quantity::new(&self.value, self.unit?)
+  }
+
+  /// The raw unit code, which is also available if the unit is not known to this crate.
+  pub fn unit_code(&self) -> Option<u8> {
+    self.unit_code
+  }
+
+  /// Serialize this register with its unit formatted using `formatter`.
+  #[cfg(feature = "serde")]
+  pub fn with_unit_formatter<'a, F: UnitFormatter + ?Sized>(
+    &'a self,
+    formatter: &'a F,
+  ) -> WithUnitFormatter<'a, Self, F> {
+    WithUnitFormatter { value: self, formatter }
+  }
+
+  fn parse_obis_code(input: &[Data]) -> IResult<&[Data], ObisCode> {
+    if let Some(data) = input.first() {
+      match data {
+        Data::OctetString(obis_code) => {
+          // Some meters pad OBIS codes, so ignore any bytes after the first six.
+          let (_, code) = ObisCode::parse(obis_code).map_err(|e| e.map_input(|_| input))?;
+          Ok((&input[1..], code))
+        },
+        _ => fail(input),
+      }
+    } else {
+      Err(nom::Err::Incomplete(nom::Needed::new(1)))
+    }
+  }
+
+  fn parse_value(input: &[Data]) -> IResult<&[Data], &Data> {
+    if let Some(data) = input.first() {
+      Ok((&input[1..], data))
+    } else {
+      Err(nom::Err::Incomplete(nom::Needed::new(1)))
+    }
+  }
+
+  fn parse_scaler_unit(input: &[Data]) -> IResult<&[Data], (i8, u8)> {
+    if let Some(data) = input.first() {
+      match data {
+        Data::Structure(data) if data.len() == 2 => {
+          // Some meters encode the scaler as a `Long` or `Unsigned` instead of an `Integer`.
+          let scaler = match data[0] {
+            Data::Integer(scaler) => Some(scaler),
+            Data::Long(scaler) => i8::try_from(scaler).ok(),
+            Data::Unsigned(scaler) => i8::try_from(scaler).ok(),
+            _ => None,
+          };
+
+          if let (Some(scaler), Data::Enum(unit)) = (scaler, &data[1]) {
+            if scaler != 0x00 || *unit != 0xff {
+              return Ok((&input[1..], (scaler, *unit)))
+            }
+          }
+        },
+        _ => (),
+      }
+
+      fail(input)
+    } else {
+      Err(nom::Err::Incomplete(nom::Needed::new(1)))
+    }
+  }
+
+  fn parse_inner_nested(input: &[Data]) -> IResult<&[Data], Self> {
+    if let Some(data) = input.first() {
+      if let Data::Structure(ref data) = data {
+        let (_, inner) = complete(Self::parse_inner)(data)?;
+        return Ok((&input[1..], inner))
+      }
+
+      fail(input)
+    } else {
+      Err(nom::Err::Incomplete(nom::Needed::new(1)))
+    }
+  }
+
+  /// Create a register from its raw value, scaler and unit code, keeping units unknown to this crate
+  /// with only their raw unit code.
+  fn with_scaler_unit(obis_code: ObisCode, value: Data, scaler: i8, unit_code: u8) -> Self {
+    let (value, raw_value) = Self::scale(value, scaler);
+    Self {
+      obis_code,
+      value,
+      raw_value,
+      scaler: Some(scaler),
+      unit: Unit::try_from(unit_code).ok(),
+      unit_code: Some(unit_code),
+    }
+  }
+
+  fn parse_inner(input: &[Data]) -> IResult<&[Data], Self> {
+    let (input, obis_code) = Self::parse_obis_code(input)?;
+
+    let (rest, value) = Self::parse_value(input)?;
+    if let Ok((rest, (scaler, unit_code))) = Self::parse_scaler_unit(rest) {
+      return Ok((rest, Self::with_scaler_unit(obis_code, value.clone(), scaler, unit_code)))
+    }
+
+    // Some meters send the scaler and unit before the value. Only try this if the value is not
+    // followed by the end of the input or the next register, since a value may itself look like
+    // a scaler and unit.
+    if !rest.is_empty() && Self::parse_obis_code(rest).is_err() {
+      if let Ok((rest, (scaler, unit_code))) = Self::parse_scaler_unit(input) {
+        if let Ok((rest, value)) = Self::parse_value(rest) {
+          return Ok((rest, Self::with_scaler_unit(obis_code, value.clone(), scaler, unit_code)))
+        }
+      }
+    }
+
+    Ok((rest, Self::new(obis_code, value.clone(), None)))
+  }
+
+  /// Parse a single register encoded as a structure of its OBIS code, value and, optionally, its scaler and unit.
+  ///
+  /// ```
+  /// use dlms_cosem::{Data, ObisCode, Register};
+  ///
+  /// let bytes = [0x02, 0x02, 0x09, 0x06, 0x01, 0x00, 0x01, 0x08, 0x00, 0xff, 0x06, 0x00, 0x00, 0x04, 0xd2];
+  /// let register = Register::parse_bytes(&bytes).unwrap();
+  /// assert_eq!(register.obis_code(), &ObisCode::new(1, 0, 1, 8, 0, 255));
+  /// assert_eq!(register.value(), &Data::DoubleLongUnsigned(1234));
+  /// ```
+  pub fn parse_bytes(input: &[u8]) -> Result<Self, Error> {
+    let (_, data) = all_consuming(complete(Data::parse))(input).map_err(|_| Error::InvalidFormat)?;
+
+    match data {
+      Data::Structure(items) => {
+        let (_, register) = all_consuming(Self::parse)(&items).map_err(|_| Error::InvalidFormat)?;
+        Ok(register)
+      },
+      _ => Err(Error::InvalidFormat),
+    }
+  }
+
+  pub(crate) fn parse(input: &[Data]) -> IResult<&[Data], Self> {
+    alt((complete(Self::parse_inner), complete(Self::parse_inner_nested)))(input)
+  }
+
+  /// Parse all registers in `input`, descending into structures and arrays which
+  /// wrap multiple registers up to `depth` levels deep.
+  fn parse_all<'d>(
+    input: &'d [Data],
+    depth: usize,
+    registers: &mut BTreeMap<ObisCode, Register>,
+  ) -> IResult<&'d [Data], ()> {
+    let (input, ()) = Self::parse_partial(input, depth, registers)?;
+    if !input.is_empty() {
+      return fail(input)
+    }
+
+    Ok((input, ()))
+  }
+
+  /// Like [`parse_all`](Self::parse_all), but stop at the first item which is not a
+  /// register and return the remaining items.
+  pub(crate) fn parse_partial<'d>(
+    mut input: &'d [Data],
+    depth: usize,
+    registers: &mut BTreeMap<ObisCode, Register>,
+  ) -> IResult<&'d [Data], ()> {
+    while let Some(data) = input.first() {
+      if let Ok((rest, reg)) = Self::parse(input) {
+        registers.insert(reg.obis_code, reg);
+        input = rest;
+        continue
+      }
+
+      match data {
+        Data::Structure(data) | Data::Array(data) if depth > 0 => {
+          // Only keep the registers of a nested structure or array if all of it could be parsed.
+          let mut nested = BTreeMap::new();
+          if Self::parse_all(data, depth - 1, &mut nested).is_err() {
+            break
+          }
+          registers.extend(nested);
+          input = &input[1..];
+        },
+        // Some meters report a register which could not be read as a bare data-access-result
+        // in its place, so skip it instead of discarding the remaining registers.
+        Data::Enum(result) if DataAccessResult::try_from(*result).is_ok() => input = &input[1..],
+        _ => break,
+      }
+    }
+
+    Ok((input, ()))
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Register {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    self.with_unit_formatter(&DefaultUnitFormatter).serialize(serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<F: UnitFormatter + ?Sized> Serialize for WithUnitFormatter<'_, Register, F> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let register = self.value;
+    let mut s = serializer.serialize_struct("Register", 6)?;
+    s.serialize_field("obis_code", &register.obis_code)?;
+    s.serialize_field("value", &register.value)?;
+    s.serialize_field("raw_value", register.raw_value())?;
+    s.serialize_field("scaler", &register.scaler)?;
+    s.serialize_field("unit", &register.unit.and_then(|unit| self.formatter.format(unit)))?;
+    s.serialize_field("unit_code", &register.unit_code)?;
+    s.end()
+  }
+}
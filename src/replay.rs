@@ -0,0 +1,45 @@
+use alloc::collections::btree_map::{BTreeMap, Entry};
+
+use crate::{Error, Metadata};
+
+/// Replay protection state, tracking the last invocation counter seen for each system title.
+///
+/// A [`Dlms`](crate::Dlms) created with [`with_replay_protection`](crate::Dlms::with_replay_protection)
+/// keeps a `ReplayWindow` internally. Without `std`, a `ReplayWindow` can be managed separately
+/// and checked against the [`Metadata`] returned by [`decrypt_with_meta`](crate::Dlms::decrypt_with_meta).
+#[derive(Debug, Default, Clone)]
+pub struct ReplayWindow {
+  invocation_counters: BTreeMap<[u8; 8], u32>,
+}
+
+impl ReplayWindow {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Check that the invocation counter of a decoded frame is greater than the last one seen
+  /// from the same system title, and remember it if so.
+  ///
+  /// Frames without an invocation counter are always accepted. Only check frames which were
+  /// decrypted and decoded successfully, so a forged or malformed frame cannot advance the window.
+  pub fn check(&mut self, meta: &Metadata) -> Result<(), Error> {
+    let (system_title, invocation_counter) = match (meta.system_title(), meta.invocation_counter()) {
+      (Some(system_title), Some(invocation_counter)) => (system_title, invocation_counter),
+      _ => return Ok(()),
+    };
+
+    match self.invocation_counters.entry(*system_title) {
+      Entry::Vacant(entry) => {
+        entry.insert(invocation_counter);
+      },
+      Entry::Occupied(mut entry) => {
+        if invocation_counter <= *entry.get() {
+          return Err(Error::Replay)
+        }
+        entry.insert(invocation_counter);
+      },
+    }
+
+    Ok(())
+  }
+}
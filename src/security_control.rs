@@ -1,6 +1,8 @@
 use core::fmt;
 
 use nom::{number::complete::u8, IResult};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct SecurityControl {
@@ -19,6 +21,22 @@ impl fmt::Debug for SecurityControl {
   }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for SecurityControl {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let mut s = serializer.serialize_struct("SecurityControl", 5)?;
+    s.serialize_field("suite_id", &self.suite_id())?;
+    s.serialize_field("authentication", &self.authentication())?;
+    s.serialize_field("encryption", &self.encryption())?;
+    s.serialize_field("broadcast", &self.broadcast())?;
+    s.serialize_field("compression", &self.compression())?;
+    s.end()
+  }
+}
+
 impl SecurityControl {
   #[rustfmt::skip]
   const COMPRESSION_BIT:    u8 = 0b10000000;
@@ -34,6 +52,12 @@ impl SecurityControl {
     Ok((input, Self { security_control }))
   }
 
+  /// The raw security control byte, which is part of the associated data of authenticated APDUs.
+  #[cfg(feature = "alloc")]
+  pub(crate) fn bits(&self) -> u8 {
+    self.security_control
+  }
+
   pub fn suite_id(&self) -> u8 {
     self.security_control & 0b00001111
   }
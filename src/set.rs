@@ -0,0 +1,74 @@
+use nom::{bytes::streaming::tag, IResult};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+  data::parse_optional, CosemAttributeDescriptor, Data, DataAccessResult, InvokeIdAndPriority,
+  SelectiveAccessDescriptor,
+};
+
+/// A `SET-Request` APDU.
+///
+/// Only the `set-request-normal` form is supported.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetRequest {
+  pub(crate) invoke_id_and_priority: InvokeIdAndPriority,
+  pub(crate) attribute_descriptor: CosemAttributeDescriptor,
+  pub(crate) access_selection: Option<SelectiveAccessDescriptor>,
+  pub(crate) value: Data,
+}
+
+impl SetRequest {
+  pub fn invoke_id_and_priority(&self) -> &InvokeIdAndPriority {
+    &self.invoke_id_and_priority
+  }
+
+  pub fn attribute_descriptor(&self) -> &CosemAttributeDescriptor {
+    &self.attribute_descriptor
+  }
+
+  pub fn access_selection(&self) -> Option<&SelectiveAccessDescriptor> {
+    self.access_selection.as_ref()
+  }
+
+  pub fn value(&self) -> &Data {
+    &self.value
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, _) = tag([1])(input)?;
+    let (input, invoke_id_and_priority) = InvokeIdAndPriority::parse(input)?;
+    let (input, attribute_descriptor) = CosemAttributeDescriptor::parse(input)?;
+    let (input, access_selection) = parse_optional(SelectiveAccessDescriptor::parse)(input)?;
+    let (input, value) = Data::parse(input)?;
+    Ok((input, Self { invoke_id_and_priority, attribute_descriptor, access_selection, value }))
+  }
+}
+
+/// A `SET-Response` APDU.
+///
+/// Only the `set-response-normal` form is supported.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetResponse {
+  pub(crate) invoke_id_and_priority: InvokeIdAndPriority,
+  pub(crate) result: DataAccessResult,
+}
+
+impl SetResponse {
+  pub fn invoke_id_and_priority(&self) -> &InvokeIdAndPriority {
+    &self.invoke_id_and_priority
+  }
+
+  pub fn result(&self) -> DataAccessResult {
+    self.result
+  }
+
+  pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    let (input, _) = tag([1])(input)?;
+    let (input, invoke_id_and_priority) = InvokeIdAndPriority::parse(input)?;
+    let (input, result) = DataAccessResult::parse(input)?;
+    Ok((input, Self { invoke_id_and_priority, result }))
+  }
+}
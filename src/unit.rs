@@ -159,9 +159,90 @@ impl Unit {
       Self::DezibelMilliwatt           => "dBm",
       Self::DezibelMicrovolt           => "dBµV",
       Self::Dezibel                    => "dB",
-      Self::Other | Self::Count        => return None,
+      // Unitless, e.g. the number of pulses of a counter.
+      Self::Count                      => "",
+      Self::Other                      => return None,
     })
   }
+
+  /// Like [`as_str`](Self::as_str), but render [`Unit::Currency`] as the given `currency`
+  /// instead of the placeholder `"currency"`.
+  ///
+  /// The currency itself is not part of the unit, so it has to be provided separately,
+  /// e.g. as the ISO 4217 code configured for the meter. Use [`CurrencyUnitFormatter`]
+  /// to serialize registers this way.
+  ///
+  /// ```
+  /// use dlms_cosem::Unit;
+  ///
+  /// assert_eq!(Unit::Currency.as_str_with_currency("EUR"), Some("EUR"));
+  /// assert_eq!(Unit::WattHour.as_str_with_currency("EUR"), Some("Wh"));
+  /// ```
+  pub fn as_str_with_currency<'a>(&self, currency: &'a str) -> Option<&'a str> {
+    match self {
+      Self::Currency => Some(currency),
+      unit => unit.as_str(),
+    }
+  }
+}
+
+/// A table of textual representations for units, e.g. for localized display.
+///
+/// The default implementation uses [`Unit::as_str`].
+///
+/// ```
+/// use dlms_cosem::{Unit, UnitFormatter};
+///
+/// struct Localized;
+///
+/// impl UnitFormatter for Localized {
+///   fn format(&self, unit: Unit) -> Option<&str> {
+///     match unit {
+///       Unit::Year => Some("Jahr"),
+///       unit => unit.as_str(),
+///     }
+///   }
+/// }
+///
+/// assert_eq!(Localized.format(Unit::Year), Some("Jahr"));
+/// assert_eq!(Localized.format(Unit::WattHour), Some("Wh"));
+/// ```
+pub trait UnitFormatter {
+  /// Format `unit`, or return `None` if it has no textual representation.
+  fn format(&self, unit: Unit) -> Option<&str> {
+    unit.as_str()
+  }
+}
+
+/// The [`UnitFormatter`] using [`Unit::as_str`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultUnitFormatter;
+
+impl UnitFormatter for DefaultUnitFormatter {}
+
+/// The [`UnitFormatter`] using [`Unit::as_str_with_currency`].
+///
+/// ```
+/// use dlms_cosem::{CurrencyUnitFormatter, Unit, UnitFormatter};
+///
+/// assert_eq!(CurrencyUnitFormatter("EUR").format(Unit::Currency), Some("EUR"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyUnitFormatter<'a>(pub &'a str);
+
+impl UnitFormatter for CurrencyUnitFormatter<'_> {
+  fn format(&self, unit: Unit) -> Option<&str> {
+    unit.as_str_with_currency(self.0)
+  }
+}
+
+/// Serializes `T` with its units formatted using `F`, see [`Register::with_unit_formatter`](crate::Register::with_unit_formatter)
+/// and [`ObisMap::with_unit_formatter`](crate::ObisMap::with_unit_formatter).
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub struct WithUnitFormatter<'a, T: ?Sized, F: ?Sized> {
+  pub(crate) value: &'a T,
+  pub(crate) formatter: &'a F,
 }
 
 impl fmt::Display for Unit {
@@ -0,0 +1,80 @@
+use alloc::borrow::Cow;
+
+use nom::{
+  bytes::streaming::{tag, take},
+  number::streaming::be_u16,
+  IResult,
+};
+
+use crate::{map_nom_error, DlmsDataLinkLayer, DlmsDataLinkLayerHeader, Error};
+
+/// The source and destination wrapper ports of a wrapper protocol data unit.
+///
+/// On the server side, the wrapper port identifies the logical device, on the
+/// client side it identifies the client application process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WrapperAddress {
+  pub source: u16,
+  pub destination: u16,
+}
+
+impl WrapperAddress {
+  /// The wrapper port of the management logical device.
+  pub const MANAGEMENT_LOGICAL_DEVICE: u16 = 0x0001;
+  /// The wrapper port of the public client.
+  pub const PUBLIC_CLIENT: u16 = 0x0010;
+}
+
+/// The header of a wrapper protocol data unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrapperHeader {
+  address: WrapperAddress,
+}
+
+impl WrapperHeader {
+  pub fn address(&self) -> WrapperAddress {
+    self.address
+  }
+
+  pub fn source_wport(&self) -> u16 {
+    self.address.source
+  }
+
+  pub fn destination_wport(&self) -> u16 {
+    self.address.destination
+  }
+
+  fn parse(input: &[u8]) -> IResult<&[u8], (Self, u16), Error> {
+    let (input, _) = tag([0x00, 0x01])(input)?;
+    let (input, source) = be_u16(input)?;
+    let (input, destination) = be_u16(input)?;
+    let (input, len) = be_u16(input)?;
+
+    Ok((input, (Self { address: WrapperAddress { source, destination } }, len)))
+  }
+}
+
+/// Data link layer for the wrapper protocol used for DLMS over TCP/UDP (IEC 62056-47).
+///
+/// Each APDU is preceded by an 8-byte header containing the version, the source
+/// and destination wrapper ports and the length of the APDU.
+#[derive(Debug)]
+pub enum WrapperDataLinkLayer {}
+
+impl<'i> DlmsDataLinkLayer<'i, &'i [u8]> for WrapperDataLinkLayer {
+  fn next_frame(input: &'i [u8]) -> Result<(&'i [u8], Cow<'i, [u8]>), Error> {
+    let (input, frame, _) = Self::next_frame_with_header(input)?;
+    Ok((input, frame))
+  }
+}
+
+impl<'i> DlmsDataLinkLayerHeader<'i, &'i [u8]> for WrapperDataLinkLayer {
+  type Header = WrapperHeader;
+
+  fn next_frame_with_header(input: &'i [u8]) -> Result<(&'i [u8], Cow<'i, [u8]>, WrapperHeader), Error> {
+    let (input, (header, len)) = map_nom_error(WrapperHeader::parse(input))?;
+    let (input, apdu) = map_nom_error(take::<_, _, Error>(len)(input))?;
+
+    Ok((input, Cow::Borrowed(apdu), header))
+  }
+}